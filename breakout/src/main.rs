@@ -0,0 +1,421 @@
+use computer::arcade::{Arcade, Scenario};
+use computer::image::{self, Palette};
+use computer::joystick::PredictTrajectory;
+use computer::recording::{Recording, Replay};
+use parser::Parser;
+use std::env;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process;
+#[cfg(feature = "raw-terminal")]
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static USAGE: &str = "Usage: breakout [--free-play] [--autoplay] [--tick <ms>] [--record <file>] \
+                      [--input <file>]\n       \
+                      breakout --replay <file> [--tick <ms>] [--speed <factor>] [--seek <frame>]\n       \
+                      breakout --replay <file> --export <dir> [--scale <n>] [--format ppm|pbm]\n\
+                      Keys: a/left, s/down/space (neutral), d/right, q quits; they are\n\
+                      read through stty, or after Enter when built with --no-default-features\n\
+                      S quick-saves, L quick-loads, r rewinds two seconds\n\
+                      Replay keys: space pauses, left/right step a frame, +/- change speed";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Left,
+    Neutral,
+    Right,
+    Faster,
+    Slower,
+    QuickSave,
+    QuickLoad,
+    Rewind,
+    Quit,
+}
+
+const DAY13_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../day13");
+
+// How far back `r` goes, and how many frames of history are kept for it.
+const REWIND_MILLIS: u128 = 2000;
+const REWIND_FRAMES: usize = 1000;
+
+struct Options {
+    free_play: bool,
+    autoplay: bool,
+    tick: Duration,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    speed: f64,
+    seek: usize,
+    export: Option<PathBuf>,
+    scale: usize,
+    format: String,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        free_play: false,
+        autoplay: false,
+        tick: Duration::from_millis(80),
+        record: None,
+        replay: None,
+        speed: 1.0,
+        seek: 0,
+        export: None,
+        scale: 4,
+        format: String::from("ppm"),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--free-play" => options.free_play = true,
+            "--autoplay" => options.autoplay = true,
+            "--record" => {
+                let path = args.next().ok_or("--record needs a file")?;
+                options.record = Some(PathBuf::from(path));
+            }
+            "--replay" => {
+                let path = args.next().ok_or("--replay needs a file")?;
+                options.replay = Some(PathBuf::from(path));
+            }
+            "--speed" => {
+                let value = args.next().ok_or("--speed needs a value")?;
+                options.speed = value
+                    .parse()
+                    .ok()
+                    .filter(|speed: &f64| *speed > 0.0)
+                    .ok_or(format!("Invalid speed {:?}", value))?;
+            }
+            "--export" => {
+                let path = args.next().ok_or("--export needs a directory")?;
+                options.export = Some(PathBuf::from(path));
+            }
+            "--scale" => {
+                let value = args.next().ok_or("--scale needs a value")?;
+                options.scale = value
+                    .parse()
+                    .map_err(|_| format!("Invalid scale {:?}", value))?;
+            }
+            "--format" => {
+                let format = args.next().ok_or("--format needs ppm or pbm")?;
+                if format != "ppm" && format != "pbm" {
+                    return Err(format!("Unknown image format {:?}", format));
+                }
+                options.format = format;
+            }
+            "--seek" => {
+                let value = args.next().ok_or("--seek needs a frame")?;
+                options.seek = value
+                    .parse()
+                    .map_err(|_| format!("Invalid frame {:?}", value))?;
+            }
+            "--tick" => {
+                let value = args.next().ok_or("--tick needs a value")?;
+                let millis = value
+                    .parse()
+                    .map_err(|_| format!("Invalid tick {:?}", value))?;
+                options.tick = Duration::from_millis(millis);
+            }
+            // Read by the input lookup.
+            "--input" => {
+                args.next().ok_or("--input needs a file")?;
+            }
+            _ if arg.starts_with("--input=") => {}
+            _ => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+// Switching off line buffering needs `stty`, an external program, so it is
+// behind the `raw-terminal` feature. Without it only std and ANSI escape codes
+// are used and keys are read a line at a time.
+#[cfg(feature = "raw-terminal")]
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Hides the cursor, and with `raw-terminal` puts the terminal in unbuffered,
+// no-echo mode. Everything is put back when dropped.
+struct RawTerminal {
+    #[cfg(feature = "raw-terminal")]
+    saved: String,
+}
+
+impl RawTerminal {
+    fn enable() -> Result<Self, String> {
+        #[cfg(feature = "raw-terminal")]
+        let saved = stty(&["-g"]).ok_or("stdin is not a terminal")?;
+        #[cfg(feature = "raw-terminal")]
+        stty(&["-icanon", "-echo", "min", "1"]).ok_or("Could not set the terminal mode")?;
+        print!("\x1b[?25l\x1b[2J");
+        Ok(RawTerminal {
+            #[cfg(feature = "raw-terminal")]
+            saved,
+        })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        #[cfg(feature = "raw-terminal")]
+        stty(&[&self.saved]);
+        print!("\x1b[?25h");
+        io::stdout().flush().ok();
+    }
+}
+
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let key = match bytes[i..] {
+            [0x1b, b'[', arrow, ..] => {
+                i += 2;
+                match arrow {
+                    b'D' => Some(Key::Left),
+                    b'C' => Some(Key::Right),
+                    b'B' => Some(Key::Neutral),
+                    _ => None,
+                }
+            }
+            [b'a' | b'h', ..] => Some(Key::Left),
+            [b'd' | b'l', ..] => Some(Key::Right),
+            [b's' | b'j' | b' ', ..] => Some(Key::Neutral),
+            [b'+' | b'=', ..] => Some(Key::Faster),
+            [b'-', ..] => Some(Key::Slower),
+            [b'S', ..] => Some(Key::QuickSave),
+            [b'L', ..] => Some(Key::QuickLoad),
+            [b'r', ..] => Some(Key::Rewind),
+            [b'q', ..] => Some(Key::Quit),
+            _ => None,
+        };
+        keys.extend(key);
+        i += 1;
+    }
+    keys
+}
+
+fn spawn_keyboard() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 16];
+        let mut stdin = io::stdin();
+        while let Ok(read) = stdin.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            for key in parse_keys(&buffer[..read]) {
+                if sender.send(key).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+fn draw(hud: String, arcade: &Arcade) {
+    let frame = format!("\x1b[H{}\x1b[K\n{}", hud, arcade.render());
+    print!("{}", frame);
+    io::stdout().flush().ok();
+}
+
+fn draw_game(scenario: &Scenario) {
+    let result = scenario.result();
+    let hud = format!(
+        "Score: {:>8}   Blocks: {:>4}",
+        result.score, result.blocks_remaining
+    );
+    draw(hud, scenario.arcade());
+}
+
+fn play(memory: Vec<i64>, options: &Options) -> Result<(), String> {
+    let terminal = RawTerminal::enable()?;
+    let keys = spawn_keyboard();
+    let mut scenario = Scenario::new(memory);
+    if options.record.is_some() {
+        scenario.start_recording();
+    }
+    scenario.enable_rewind(REWIND_FRAMES);
+    let rewind_frames = (REWIND_MILLIS / options.tick.as_millis().max(1)).max(1) as usize;
+    let mut autoplay = PredictTrajectory::default();
+    let mut joystick = 0;
+    let mut next_tick = Instant::now();
+    let mut quit = false;
+    while !quit && scenario.run_until_input()? {
+        draw_game(&scenario);
+        next_tick += options.tick;
+        thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+        // The joystick stays where it was last pushed.
+        let mut restored = false;
+        for key in keys.try_iter() {
+            match key {
+                Key::Left => joystick = -1,
+                Key::Neutral => joystick = 0,
+                Key::Right => joystick = 1,
+                Key::QuickSave => scenario.quick_save(),
+                Key::QuickLoad => restored |= scenario.quick_load(),
+                Key::Rewind => {
+                    let decisions = rewind_frames.min(scenario.rewind_points().saturating_sub(1));
+                    restored |= scenario.rewind(decisions);
+                }
+                Key::Quit => quit = true,
+                _ => {}
+            }
+        }
+        if restored {
+            // The ball jumped, so its last position means nothing now.
+            autoplay = PredictTrajectory::default();
+            continue;
+        }
+        if options.autoplay {
+            joystick = scenario.decide(&mut autoplay);
+        }
+        scenario.push_joystick(joystick);
+    }
+    draw_game(&scenario);
+    drop(terminal);
+
+    let result = scenario.result();
+    let outcome = if quit {
+        "Quit"
+    } else {
+        &format!("{:?}", result.status)
+    };
+    println!(
+        "{}: score {} after {} frames, {} blocks broken, {} left",
+        outcome, result.score, result.frames, result.blocks_broken, result.blocks_remaining
+    );
+    if let Some(frame) = result.missed_at {
+        println!("Ball missed at frame {}", frame);
+    }
+    if let (Some(path), Some(recording)) = (&options.record, scenario.take_recording()) {
+        recording.save(path)?;
+        println!(
+            "Recorded {} frames to {}",
+            recording.frames(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn replay(recording: Recording, options: &Options) -> Result<(), String> {
+    let mut replay = Replay::new(recording)?;
+    replay.seek(options.seek)?;
+    let terminal = RawTerminal::enable()?;
+    let keys = spawn_keyboard();
+    let mut speed = options.speed;
+    let mut paused = false;
+    loop {
+        let joystick = match replay.joystick() {
+            Some(-1) => "left",
+            Some(1) => "right",
+            Some(_) => "neutral",
+            None => "-",
+        };
+        let hud = format!(
+            "Frame: {:>6}/{}   Score: {:>8}   Joystick: {:<7}   Speed: {}x{}",
+            replay.frame(),
+            replay.frames().saturating_sub(1),
+            replay.score(),
+            joystick,
+            speed,
+            if paused { "   (paused)" } else { "" }
+        );
+        draw(hud, replay.arcade());
+        thread::sleep(options.tick.div_f64(speed));
+        let mut frame = replay.frame();
+        for key in keys.try_iter() {
+            match key {
+                Key::Neutral => paused = !paused,
+                Key::Left => {
+                    paused = true;
+                    frame = frame.saturating_sub(1);
+                }
+                Key::Right => {
+                    paused = true;
+                    frame += 1;
+                }
+                Key::Faster => speed *= 2.0,
+                Key::Slower => speed /= 2.0,
+                Key::Quit => {
+                    drop(terminal);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        if !paused {
+            frame += 1;
+        }
+        replay.seek(frame)?;
+    }
+}
+
+fn run(options: Options) -> Result<(), String> {
+    if let Some(path) = &options.replay {
+        let recording = Recording::load(path)?;
+        if let Some(directory) = &options.export {
+            let frames = image::export_frames(
+                recording,
+                directory,
+                &options.format,
+                &Palette::arcade(),
+                options.scale,
+            )?;
+            println!("Exported {} frames to {}", frames, directory.display());
+            return Ok(());
+        }
+        return replay(recording, &options);
+    }
+    // Plays the day 13 cabinet, found the same way day13 finds its input.
+    let source = parser::input::locate("day13", DAY13_DIR, None);
+    let mut memory = source.read().map_err(|error| error.to_string())?;
+    if options.free_play {
+        let path = parser::input::data_path(DAY13_DIR, "free_play.patch");
+        Parser::try_patch_from_txt(&path)
+            .map_err(|error| error.to_string())?
+            .apply(&mut memory);
+    }
+    play(memory, &options)
+}
+
+fn main() {
+    let result = parse_args(env::args().skip(1)).and_then(run);
+    if let Err(error) = result {
+        eprintln!("{}\n{}", error, USAGE);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b"a\x1b[C \x1b[D+-qx"),
+            vec![
+                Key::Left,
+                Key::Right,
+                Key::Neutral,
+                Key::Left,
+                Key::Faster,
+                Key::Slower,
+                Key::Quit
+            ]
+        );
+    }
+}
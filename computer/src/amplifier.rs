@@ -1,6 +1,9 @@
 use crate::computer::Computer;
+use crate::trace::SignalTrace;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
 
 #[derive(Default)]
 pub struct Amplifier {
@@ -8,36 +11,131 @@ pub struct Amplifier {
     phase_setting: Rc<RefCell<i64>>,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CircuitMode {
+    Serial,
+    #[default]
+    Feedback,
+}
+
 #[derive(Default)]
 pub struct AmplificationCircuit {
-    amplifiers: [Amplifier; 5],
+    amplifiers: Vec<Amplifier>,
     phase_settings: PhaseSettings,
+    mode: CircuitMode,
+    trace: SignalTrace,
 }
 
 pub type PhaseSettings = Vec<Rc<RefCell<i64>>>;
 
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StageStats {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub steps: usize,
+    pub last_output: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PipelineResult {
+    pub signal: i64,
+    pub stages: Vec<StageStats>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhaseSearch {
+    pub signal: i64,
+    pub phases: Vec<i64>,
+    pub ranking: Option<Vec<(Vec<i64>, i64)>>,
+}
+
+// Distinct orderings only: repeated phase values do not give repeated runs.
+fn permutations(alphabet: &[i64]) -> Vec<Vec<i64>> {
+    if alphabet.len() <= 1 {
+        return vec![alphabet.to_vec()];
+    }
+    let mut result = Vec::new();
+    for (i, first) in alphabet.iter().enumerate() {
+        if alphabet[..i].contains(first) {
+            continue;
+        }
+        let mut rest = alphabet.to_vec();
+        rest.remove(i);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, *first);
+            result.push(permutation);
+        }
+    }
+    result
+}
+
+// Runs one amplifier until it halts, or until its input channel is closed
+// while it waits for a value.
+fn run_stage(
+    mut computer: Computer,
+    receiver: Receiver<i64>,
+    sender: Sender<i64>,
+) -> Result<(Computer, StageStats), String> {
+    let mut stats = StageStats::default();
+    loop {
+        match computer.process_queued()? {
+            Some(value) => {
+                stats.outputs += 1;
+                stats.last_output = Some(value);
+                // The next stage may already have halted.
+                let _ = sender.send(value);
+            }
+            None if computer.is_waiting_input() => match receiver.recv() {
+                Ok(value) => {
+                    stats.inputs += 1;
+                    computer.push_input(value);
+                }
+                Err(_) => break,
+            },
+            None => break,
+        }
+    }
+    stats.steps = computer.get_steps();
+    Ok((computer, stats))
+}
+
 impl From<Vec<i64>> for AmplificationCircuit {
     fn from(program: Vec<i64>) -> Self {
-        let mut result: AmplificationCircuit = AmplificationCircuit::default();
-        let phase_settings: PhaseSettings = vec![
-            Rc::new(RefCell::new(0)),
-            Rc::new(RefCell::new(1)),
-            Rc::new(RefCell::new(2)),
-            Rc::new(RefCell::new(3)),
-            Rc::new(RefCell::new(4)),
-        ];
-        for i in 0..phase_settings.len() {
-            result.amplifiers[i] = Amplifier {
-                computer: Computer::new(program.clone()),
-                phase_setting: Rc::clone(&phase_settings[i]),
-            };
-        }
-        result.phase_settings = phase_settings;
-        result
+        AmplificationCircuit::new(program, 5, CircuitMode::Feedback)
     }
 }
 
 impl AmplificationCircuit {
+    pub fn new(program: Vec<i64>, size: usize, mode: CircuitMode) -> Self {
+        let phase_settings: PhaseSettings =
+            (0..size).map(|i| Rc::new(RefCell::new(i as i64))).collect();
+        let amplifiers = phase_settings
+            .iter()
+            .map(|phase_setting| Amplifier {
+                computer: Computer::new(program.clone()),
+                phase_setting: Rc::clone(phase_setting),
+            })
+            .collect();
+        AmplificationCircuit {
+            amplifiers,
+            phase_settings,
+            mode,
+            trace: SignalTrace::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.amplifiers.len()
+    }
+
+    pub fn mode(&self) -> CircuitMode {
+        self.mode
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.amplifiers.is_empty()
+    }
+
     pub fn set_phase_setting(&mut self, phase_settings: Vec<i64>) {
         for (i, setting) in phase_settings.iter().enumerate() {
             *self.phase_settings[i].borrow_mut() = *setting;
@@ -48,16 +146,334 @@ impl AmplificationCircuit {
                 .set_phase_setting(*amplifier.phase_setting.borrow());
         }
     }
-    pub fn process(&mut self) -> i64 {
+    pub fn process(&mut self) -> Result<i64, String> {
+        match self.mode {
+            CircuitMode::Serial => self.process_serial(),
+            CircuitMode::Feedback => self.process_feedback(),
+        }
+    }
+
+    pub fn trace(&self) -> &SignalTrace {
+        &self.trace
+    }
+
+    fn process_serial(&mut self) -> Result<i64, String> {
+        let size = self.amplifiers.len();
+        let mut input_signal = 0;
+        self.trace.clear();
+        self.trace.record(0, None, Some(0), input_signal);
+        for (i, amplifier) in self.amplifiers.iter_mut().enumerate() {
+            amplifier.computer.process(input_signal, false)?;
+            input_signal = amplifier.computer.get_output();
+            let next = (i + 1 < size).then_some(i + 1);
+            self.trace.record(0, Some(i), next, input_signal);
+        }
+        Ok(input_signal)
+    }
+
+    fn process_feedback(&mut self) -> Result<i64, String> {
+        let size = self.amplifiers.len();
         let mut input_signal = 0;
         let mut last_running = true;
+        let mut iteration = 0;
+        self.trace.clear();
+        self.trace.record(0, None, Some(0), input_signal);
         while last_running {
-            for amplifier in self.amplifiers.iter_mut() {
-                amplifier.computer.process(input_signal, true);
+            for (i, amplifier) in self.amplifiers.iter_mut().enumerate() {
+                let halted = amplifier.computer.process(input_signal, true)?;
                 input_signal = amplifier.computer.get_output();
+                if !halted {
+                    self.trace
+                        .record(iteration, Some(i), Some((i + 1) % size), input_signal);
+                }
+            }
+            last_running = self
+                .amplifiers
+                .last()
+                .is_some_and(|amplifier| !amplifier.computer.is_finished());
+            iteration += 1;
+        }
+        let last_iteration = self.trace.iterations().saturating_sub(1);
+        self.trace
+            .record(last_iteration, size.checked_sub(1), None, input_signal);
+        Ok(input_signal)
+    }
+
+    // Tries every ordering of `alphabet` as the phase settings of a circuit
+    // with one amplifier per symbol, spreading the work over all cores.
+    pub fn optimise_phases(
+        program: &[i64],
+        alphabet: &[i64],
+        mode: CircuitMode,
+        keep_ranking: bool,
+    ) -> Result<PhaseSearch, String> {
+        let candidates = permutations(alphabet);
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = candidates.len().div_ceil(workers).max(1);
+        let mut ranking = thread::scope(|scope| {
+            let handles: Vec<_> = candidates
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|phases| {
+                                let mut amplification_circuit =
+                                    AmplificationCircuit::new(program.to_vec(), phases.len(), mode);
+                                amplification_circuit.set_phase_setting(phases.clone());
+                                Ok((phases.clone(), amplification_circuit.process()?))
+                            })
+                            .collect::<Result<Vec<_>, String>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Phase search thread panicked"))
+                .collect::<Result<Vec<_>, String>>()
+        })?
+        .concat();
+        ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let (phases, signal) = ranking.first().cloned().unwrap_or_default();
+        Ok(PhaseSearch {
+            signal,
+            phases,
+            ranking: keep_ranking.then_some(ranking),
+        })
+    }
+
+    // Same circuit as `process`, with every amplifier on its own thread and
+    // the signal passed over channels. Each phase setting is sent as the
+    // amplifier's first input.
+    pub fn process_threaded(&mut self) -> Result<PipelineResult, String> {
+        let size = self.amplifiers.len();
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..size).map(|_| mpsc::channel()).unzip();
+        // In serial mode the last amplifier writes to a channel nobody reads.
+        let (output_sender, _output_receiver) = mpsc::channel();
+        let phases: Vec<i64> = self
+            .phase_settings
+            .iter()
+            .map(|setting| *setting.borrow())
+            .collect();
+        let mut computers = Vec::new();
+        for (i, (amplifier, phase)) in self.amplifiers.iter_mut().zip(phases).enumerate() {
+            let mut computer = std::mem::take(&mut amplifier.computer);
+            // The phase goes through the input queue instead.
+            computer.take_phase_setting();
+            computer.push_input(phase);
+            if i == 0 {
+                computer.push_input(0);
+            }
+            computers.push(computer);
+        }
+
+        let handles: Vec<_> = computers
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(i, (computer, receiver))| {
+                let sender = match self.mode {
+                    CircuitMode::Serial if i + 1 == size => output_sender.clone(),
+                    _ => senders[(i + 1) % size].clone(),
+                };
+                thread::spawn(move || run_stage(computer, receiver, sender))
+            })
+            .collect();
+        drop(senders);
+        drop(output_sender);
+
+        let mut result = PipelineResult::default();
+        for (amplifier, handle) in self.amplifiers.iter_mut().zip(handles) {
+            let (computer, stats) = handle.join().expect("Amplifier thread panicked")?;
+            amplifier.computer = computer;
+            result.stages.push(stats);
+        }
+        result.signal = result
+            .stages
+            .last()
+            .and_then(|stats| stats.last_output)
+            .unwrap_or(0);
+        Ok(result)
+    }
+}
+
+// The day 7 part 2 example, shared by the circuit tests.
+#[cfg(test)]
+pub(crate) const FEEDBACK_EXAMPLE: [i64; 29] = [
+    3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28, 1005,
+    28, 6, 99, 0, 0, 5,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::TraceEntry;
+
+    #[test]
+    fn test_feedback_loop_of_any_size() {
+        let program = FEEDBACK_EXAMPLE.to_vec();
+        let mut amplification_circuit = AmplificationCircuit::from(program.clone());
+        amplification_circuit.set_phase_setting(vec![9, 8, 7, 6, 5]);
+        assert_eq!(amplification_circuit.process().unwrap(), 139629729);
+
+        let mut amplification_circuit =
+            AmplificationCircuit::new(program, 3, CircuitMode::Feedback);
+        assert_eq!(amplification_circuit.len(), 3);
+        amplification_circuit.set_phase_setting(vec![9, 8, 7]);
+        assert_eq!(amplification_circuit.process().unwrap(), 145111);
+    }
+
+    #[test]
+    fn test_serial_examples() {
+        let examples = [
+            (
+                vec![
+                    3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+                ],
+                43210,
+                vec![4, 3, 2, 1, 0],
+            ),
+            (
+                vec![
+                    3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23,
+                    23, 4, 23, 99, 0, 0,
+                ],
+                54321,
+                vec![0, 1, 2, 3, 4],
+            ),
+            (
+                vec![
+                    3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7,
+                    33, 1, 33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+                ],
+                65210,
+                vec![1, 0, 4, 3, 2],
+            ),
+        ];
+        for (program, signal, phases) in examples {
+            let mut amplification_circuit =
+                AmplificationCircuit::new(program.clone(), 5, CircuitMode::Serial);
+            amplification_circuit.set_phase_setting(phases.clone());
+            assert_eq!(amplification_circuit.process().unwrap(), signal);
+
+            let mut amplification_circuit =
+                AmplificationCircuit::new(program.clone(), 5, CircuitMode::Serial);
+            amplification_circuit.set_phase_setting(phases.clone());
+            assert_eq!(
+                amplification_circuit.process_threaded().unwrap().signal,
+                signal
+            );
+
+            let search = AmplificationCircuit::optimise_phases(
+                &program,
+                &[0, 1, 2, 3, 4],
+                CircuitMode::Serial,
+                false,
+            )
+            .unwrap();
+            assert_eq!((search.signal, search.phases), (signal, phases));
+            assert_eq!(search.ranking, None);
+        }
+    }
+
+    #[test]
+    fn test_permutations_are_distinct() {
+        assert_eq!(permutations(&[0, 1, 2]).len(), 6);
+        assert_eq!(
+            permutations(&[1, 0, 1]),
+            vec![vec![1, 0, 1], vec![1, 1, 0], vec![0, 1, 1]]
+        );
+        assert_eq!(permutations(&[5, 5, 5]), vec![vec![5, 5, 5]]);
+    }
+
+    #[test]
+    fn test_optimise_feedback_examples() {
+        let program = FEEDBACK_EXAMPLE.to_vec();
+        let search = AmplificationCircuit::optimise_phases(
+            &program,
+            &[5, 6, 7, 8, 9],
+            CircuitMode::Feedback,
+            true,
+        )
+        .unwrap();
+        assert_eq!(search.signal, 139629729);
+        assert_eq!(search.phases, vec![9, 8, 7, 6, 5]);
+        let ranking = search.ranking.unwrap();
+        assert_eq!(ranking.len(), 120);
+        assert!(ranking.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        let program = vec![
+            3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54,
+            -5, 54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4,
+            53, 1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10,
+        ];
+        let search = AmplificationCircuit::optimise_phases(
+            &program,
+            &[5, 6, 7, 8, 9],
+            CircuitMode::Feedback,
+            false,
+        )
+        .unwrap();
+        assert_eq!((search.signal, search.phases), (18216, vec![9, 7, 8, 5, 6]));
+    }
+
+    #[test]
+    fn test_signal_trace() {
+        let program = FEEDBACK_EXAMPLE.to_vec();
+        let mut amplification_circuit = AmplificationCircuit::from(program.clone());
+        amplification_circuit.set_phase_setting(vec![9, 8, 7, 6, 5]);
+        amplification_circuit.process().unwrap();
+        let best = amplification_circuit.trace().clone();
+        assert_eq!(best.iterations(), 5);
+        assert_eq!(best.entries().len(), 1 + 5 * 5 + 1);
+        assert_eq!(best.entries()[1].value, 5);
+        assert_eq!(
+            best.entries().last().unwrap(),
+            &TraceEntry {
+                iteration: 4,
+                from: Some(4),
+                to: None,
+                value: 139629729
             }
-            last_running = !self.amplifiers[4].computer.is_finished();
+        );
+        let csv = best.to_csv();
+        assert!(csv.starts_with("iteration,from,to,value\n0,input,A,0\n0,A,B,5\n"));
+        assert!(csv.ends_with("4,E,output,139629729\n"));
+
+        let mut amplification_circuit = AmplificationCircuit::from(program);
+        amplification_circuit.set_phase_setting(vec![5, 6, 7, 8, 9]);
+        amplification_circuit.process().unwrap();
+        let table = SignalTrace::compare(&[
+            (String::from("98765"), &best),
+            (String::from("56789"), amplification_circuit.trace()),
+        ]);
+        assert_eq!(table.lines().count(), 1 + 27);
+        assert!(
+            table
+                .lines()
+                .nth(1)
+                .unwrap()
+                .ends_with("input       A             0             0")
+        );
+    }
+
+    #[test]
+    fn test_threaded_feedback_loop() {
+        let program = vec![
+            3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54,
+            -5, 54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4,
+            53, 1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10,
+        ];
+        let mut amplification_circuit = AmplificationCircuit::from(program);
+        amplification_circuit.set_phase_setting(vec![9, 7, 8, 5, 6]);
+        let result = amplification_circuit.process_threaded().unwrap();
+        assert_eq!(result.signal, 18216);
+        assert_eq!(result.stages.len(), 5);
+        for stats in &result.stages {
+            assert_eq!(stats.outputs, 10);
+            assert!(stats.steps > 0);
         }
-        input_signal
+        assert_eq!(result.stages[0].inputs, 9);
+        assert_eq!(result.stages[4].last_output, Some(18216));
     }
 }
@@ -1,82 +1,132 @@
 use crate::Computer;
-use std::{time,cell::RefCell, thread::sleep};
+use crate::joystick::{FollowBall, JoystickStrategy};
+use crate::recording::{Event, Recording};
+use std::collections::VecDeque;
+use std::{cell::RefCell, thread::sleep, time};
 
-#[derive(Default, Clone, Copy, PartialEq)]
-enum Tile {
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Tile {
     #[default]
-    Empty,
-    Wall,
-    Block,
-    Paddle,
-    Ball,
+    Empty = 0,
+    Wall = 1,
+    Block = 2,
+    Paddle = 3,
+    Ball = 4,
 }
 
-impl From<i64> for Tile {
-    fn from(value: i64) -> Self {
+impl TryFrom<i64> for Tile {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
         match value {
-            0 => Tile::Empty,
-            1 => Tile::Wall,
-            2 => Tile::Block,
-            3 => Tile::Paddle,
-            4 => Tile::Ball,
-            _ => {
-                println!("Tile not supported");
-                Tile::Empty
-            }
+            0 => Ok(Tile::Empty),
+            1 => Ok(Tile::Wall),
+            2 => Ok(Tile::Block),
+            3 => Ok(Tile::Paddle),
+            4 => Ok(Tile::Ball),
+            _ => Err(format!("Unknown tile id {}", value)),
         }
     }
 }
 
 impl Tile {
-    fn print(&self) {
-        let tile = match self {
+    pub fn symbol(&self) -> char {
+        match self {
             Tile::Ball => 'o',
             Tile::Block => '#',
             Tile::Paddle => '_',
             Tile::Wall => '|',
             _ => ' ',
-        };
-        print!("{}", tile);
+        }
     }
 }
 
 type Grid = Vec<Vec<RefCell<Tile>>>;
 
-#[derive(Default)]
-struct Arcade {
+// The screen starts empty and grows to fit whatever is drawn, up to
+// MAX_SCREEN_SIDE tiles on each side.
+#[derive(Default, Clone)]
+pub struct Arcade {
     position_x: usize,
     position_y: usize,
+    width: usize,
     grid: Grid,
 }
 
+pub(crate) const MAX_SCREEN_SIDE: usize = 4096;
+
 impl Arcade {
-    fn default() -> Self {
-        let grid: Vec<Vec<RefCell<Tile>>> = vec![vec![RefCell::new(Tile::default()); 42]; 24];
-        Arcade {
-            position_x: 0,
-            position_y: 0,
-            grid,
-        }
-    }
     pub fn print_grid(&self) {
+        print!("{}", self.render());
+    }
+
+    pub fn render(&self) -> String {
+        let mut screen = String::new();
         for row in &self.grid {
-            for cell in row {
-                cell.borrow().print();
+            screen.extend(row.iter().map(|cell| cell.borrow().symbol()));
+            screen.push('\n');
+        }
+        screen
+    }
+
+    // Lit cells for the tiles `ink` accepts, e.g. to read letters drawn
+    // with blocks.
+    pub fn mask(&self, ink: impl Fn(Tile) -> bool) -> Vec<Vec<bool>> {
+        self.grid
+            .iter()
+            .map(|row| row.iter().map(|cell| ink(*cell.borrow())).collect())
+            .collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+
+    fn move_to(&mut self, x: i64, y: i64) -> Result<(), String> {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(column), Ok(row)) if column < MAX_SCREEN_SIDE && row < MAX_SCREEN_SIDE => {
+                (self.position_x, self.position_y) = (column, row);
+                Ok(())
             }
-            println!();
+            _ => Err(format!("Position ({}, {}) is off screen", x, y)),
+        }
+    }
+
+    fn grow(&mut self, width: usize, height: usize) {
+        if height > self.grid.len() {
+            self.grid.resize_with(height, Vec::new);
+        }
+        self.width = self.width.max(width);
+        for row in self.grid.iter_mut().filter(|row| row.len() < self.width) {
+            row.resize_with(self.width, RefCell::default);
         }
     }
 
-    fn move_to(&mut self, x: i64, y: i64) {
-        (self.position_x, self.position_y) = (x as usize, y as usize);
+    fn store(&mut self, tile: Tile) {
+        self.grow(self.position_x + 1, self.position_y + 1);
+        (*self.grid[self.position_y][self.position_x].borrow_mut()) = tile;
     }
 
-    pub fn set_tile(&mut self, tile: i64) {
-        (*self.grid[self.position_y][self.position_x].borrow_mut()) = Tile::from(tile);
+    pub fn set_tile(&mut self, tile: i64) -> Result<(), String> {
+        self.store(Tile::try_from(tile)?);
+        Ok(())
     }
-    pub fn set_tile_at(&mut self, x: i64, y: i64, tile: i64) {
-        self.move_to(x, y);
-        self.set_tile(tile);
+    pub fn set_tile_at(&mut self, x: i64, y: i64, tile: i64) -> Result<(), String> {
+        self.move_to(x, y)?;
+        self.set_tile(tile)
+    }
+    pub fn put_tile(&mut self, x: i64, y: i64, tile: Tile) -> Result<(), String> {
+        self.move_to(x, y)?;
+        self.store(tile);
+        Ok(())
+    }
+    pub fn tile_at(&self, x: i64, y: i64) -> Option<Tile> {
+        let row = self.grid.get(usize::try_from(y).ok()?)?;
+        row.get(usize::try_from(x).ok()?).map(|cell| *cell.borrow())
     }
     pub fn how_many_block(&self) -> usize {
         // self.grid
@@ -95,10 +145,99 @@ impl Arcade {
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum GameStatus {
+    #[default]
+    Running,
+    Won,
+    Lost,
+    // The program stopped with blocks left and the ball never missed.
+    Halted,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GameResult {
+    pub score: i64,
+    pub blocks_broken: usize,
+    pub blocks_remaining: usize,
+    pub frames: usize,
+    pub steps: usize,
+    pub status: GameStatus,
+    pub missed_at: Option<usize>,
+}
+
+pub trait Renderer {
+    fn render(&mut self, arcade: &Arcade, score: i64);
+}
+
+// Draws the screen and pauses only when the score changes.
+pub struct TerminalRenderer {
+    pub delay: time::Duration,
+    last_score: Option<i64>,
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        TerminalRenderer {
+            delay: time::Duration::from_millis(100),
+            last_score: None,
+        }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn render(&mut self, arcade: &Arcade, score: i64) {
+        if self.last_score == Some(score) {
+            return;
+        }
+        self.last_score = Some(score);
+        println!("Score: {}", score);
+        arcade.print_grid();
+        sleep(self.delay);
+    }
+}
+
 #[derive(Default)]
 pub struct Scenario {
     computer: Computer,
     arcade: Arcade,
+    score: i64,
+    ball: (i64, i64),
+    paddle: Option<(i64, i64)>,
+    frames: usize,
+    blocks_broken: usize,
+    recording: Option<Recording>,
+    missed_at: Option<usize>,
+    awaiting_joystick: bool,
+    quick_save: Option<SaveState>,
+    rewind_capacity: usize,
+    rewind_buffer: VecDeque<SaveState>,
+}
+
+// Everything needed to resume a game, taken while the game waits for the
+// joystick.
+#[derive(Clone)]
+pub struct SaveState {
+    computer: Computer,
+    arcade: Arcade,
+    score: i64,
+    ball: (i64, i64),
+    paddle: Option<(i64, i64)>,
+    frames: usize,
+    blocks_broken: usize,
+    recorded_events: usize,
+    missed_at: Option<usize>,
+    awaiting_joystick: bool,
+}
+
+impl SaveState {
+    pub fn frame(&self) -> usize {
+        self.frames
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
 }
 
 impl Scenario {
@@ -108,48 +247,263 @@ impl Scenario {
         Scenario {
             computer,
             arcade: Arcade::default(),
+            score: 0,
+            ball: (0, 0),
+            paddle: None,
+            frames: 0,
+            blocks_broken: 0,
+            recording: None,
+            missed_at: None,
+            awaiting_joystick: false,
+            quick_save: None,
+            rewind_capacity: 0,
+            rewind_buffer: VecDeque::new(),
+        }
+    }
+
+    pub fn save_state(&self) -> SaveState {
+        SaveState {
+            computer: self.computer.clone(),
+            arcade: self.arcade.clone(),
+            score: self.score,
+            ball: self.ball,
+            paddle: self.paddle,
+            frames: self.frames,
+            blocks_broken: self.blocks_broken,
+            recorded_events: self
+                .recording
+                .as_ref()
+                .map_or(0, |recording| recording.events().len()),
+            missed_at: self.missed_at,
+            awaiting_joystick: self.awaiting_joystick,
         }
     }
-    pub fn execute_scenario(&mut self) -> usize {
-        let mut run_scenario = true;
-        println!("Insert 2 coins: ");
-        let mut input_instruction = 0;
-        let mut paddle_pos = 0;
-        while run_scenario {
-            let terminate = self.computer.process(input_instruction, true);
-            if terminate {
-                break;
+
+    // Events recorded after the state was saved are dropped from the
+    // recording, so it keeps describing the game being played.
+    pub fn load_state(&mut self, state: &SaveState) {
+        self.computer = state.computer.clone();
+        self.arcade = state.arcade.clone();
+        self.score = state.score;
+        self.ball = state.ball;
+        self.paddle = state.paddle;
+        self.frames = state.frames;
+        self.blocks_broken = state.blocks_broken;
+        self.missed_at = state.missed_at;
+        self.awaiting_joystick = state.awaiting_joystick;
+        if let Some(recording) = &mut self.recording {
+            recording.truncate(state.recorded_events);
+        }
+        while self
+            .rewind_buffer
+            .back()
+            .is_some_and(|saved| saved.frames > state.frames)
+        {
+            self.rewind_buffer.pop_back();
+        }
+    }
+
+    pub fn quick_save(&mut self) {
+        self.quick_save = Some(self.save_state());
+    }
+
+    pub fn quick_load(&mut self) -> bool {
+        match self.quick_save.take() {
+            Some(state) => {
+                self.load_state(&state);
+                self.quick_save = Some(state);
+                true
             }
-            let x: i64 = self.computer.get_output();
-            self.computer.process(input_instruction, true);
-            let y = self.computer.get_output();
-            self.computer.process(input_instruction, true);
-            let tile = self.computer.get_output();
-            if tile == 4 {
-                let ball_pos = x;
-                if ball_pos > paddle_pos {
-                    input_instruction = 1;
-                } else if ball_pos < paddle_pos {
-                    input_instruction = -1;
-                } else {
-                    input_instruction = 0;
+            None => false,
+        }
+    }
+
+    // Keeps the state at each of the last `capacity` joystick reads.
+    pub fn enable_rewind(&mut self, capacity: usize) {
+        self.rewind_capacity = capacity;
+        self.rewind_buffer.clear();
+    }
+
+    pub fn rewind_points(&self) -> usize {
+        self.rewind_buffer.len()
+    }
+
+    // Goes back to the joystick read `decisions` reads ago; 0 is the current
+    // one. Returns false when the buffer does not go back that far.
+    pub fn rewind(&mut self, decisions: usize) -> bool {
+        let Some(index) = self.rewind_buffer.len().checked_sub(decisions + 1) else {
+            return false;
+        };
+        let state = self.rewind_buffer[index].clone();
+        self.load_state(&state);
+        true
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::default());
+    }
+
+    pub fn take_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    fn record(&mut self, event: Event) {
+        if let Some(recording) = &mut self.recording {
+            recording.record(self.computer.get_steps(), self.frames, event);
+        }
+    }
+
+    // Handles one x, y, tile triple. Returns whether a block was broken.
+    fn draw(&mut self, x: i64, y: i64, tile: i64) -> Result<bool, String> {
+        if x == -1 && y == 0 {
+            self.score = tile;
+            self.record(Event::Score(tile));
+            return Ok(false);
+        }
+        let was_block = self.arcade.tile_at(x, y) == Some(Tile::Block);
+        let step = |error| format!("Step {}: {}", self.computer.get_steps(), error);
+        let tile = Tile::try_from(tile).map_err(step)?;
+        self.arcade.put_tile(x, y, tile).map_err(step)?;
+        self.record(Event::Tile { x, y, tile });
+        match tile {
+            Tile::Ball => {
+                self.ball = (x, y);
+                // Reaching the paddle row anywhere but on the paddle loses it.
+                if let Some((paddle_x, paddle_y)) = self.paddle
+                    && self.missed_at.is_none()
+                    && y >= paddle_y
+                    && self.arcade.tile_at(paddle_x, paddle_y) == Some(Tile::Paddle)
+                {
+                    self.missed_at = Some(self.frames);
                 }
             }
+            Tile::Paddle => self.paddle = Some((x, y)),
+            _ => {}
+        }
+        Ok(was_block && tile != Tile::Block)
+    }
+
+    pub fn play(&mut self, renderer: Option<&mut dyn Renderer>) -> Result<GameResult, String> {
+        self.play_with(&mut FollowBall, renderer)
+    }
 
-            if tile == 3 {
-                paddle_pos = x;
+    fn is_cleared(&self) -> bool {
+        self.blocks_broken > 0 && self.arcade.how_many_block() == 0
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.missed_at.is_some() || self.is_cleared()
+    }
+
+    // Runs the game until it reads the joystick, which starts a new frame.
+    // Returns false once the game has halted, or when it reads the joystick
+    // after the ball was missed or the last block broken.
+    pub fn run_until_input(&mut self) -> Result<bool, String> {
+        if self.awaiting_joystick {
+            return Ok(true);
+        }
+        loop {
+            match self.computer.process_queued()? {
+                Some(x) => {
+                    let y = self.computer.process_queued()?;
+                    let tile = self.computer.process_queued()?;
+                    let (Some(y), Some(tile)) = (y, tile) else {
+                        return Ok(false);
+                    };
+                    if self.draw(x, y, tile)? {
+                        self.blocks_broken += 1;
+                    }
+                }
+                None if self.computer.is_waiting_input() => {
+                    if self.is_over() {
+                        return Ok(false);
+                    }
+                    self.frames += 1;
+                    self.awaiting_joystick = true;
+                    if self.rewind_capacity > 0 {
+                        if self.rewind_buffer.len() == self.rewind_capacity {
+                            self.rewind_buffer.pop_front();
+                        }
+                        self.rewind_buffer.push_back(self.save_state());
+                    }
+                    return Ok(true);
+                }
+                None => return Ok(false),
             }
-            if x == -1 {
-                println!("Score: {}", tile);
-                self.arcade.print_grid();
-                sleep(time::Duration::from_millis(100));
+        }
+    }
+
+    pub fn push_joystick(&mut self, joystick: i64) {
+        self.awaiting_joystick = false;
+        self.record(Event::Joystick(joystick.signum()));
+        self.computer.push_input(joystick.signum());
+    }
+
+    pub fn arcade(&self) -> &Arcade {
+        &self.arcade
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    pub fn ball(&self) -> (i64, i64) {
+        self.ball
+    }
+
+    pub fn paddle(&self) -> Option<(i64, i64)> {
+        self.paddle
+    }
+
+    // The joystick stays still until the paddle has been drawn.
+    pub fn decide(&self, strategy: &mut dyn JoystickStrategy) -> i64 {
+        self.paddle
+            .map_or(0, |paddle| strategy.decide(&self.arcade, self.ball, paddle))
+    }
+
+    pub fn result(&self) -> GameResult {
+        let blocks_remaining = self.arcade.how_many_block();
+        GameResult {
+            score: self.score,
+            blocks_broken: self.blocks_broken,
+            blocks_remaining,
+            frames: self.frames,
+            steps: self.computer.get_steps(),
+            status: if self.missed_at.is_some() {
+                GameStatus::Lost
+            } else if self.is_cleared() {
+                GameStatus::Won
+            } else if self.computer.is_finished() {
+                GameStatus::Halted
             } else {
-                let _ = self.arcade.set_tile_at(x, y, tile);
-            }
+                GameStatus::Running
+            },
+            missed_at: self.missed_at,
+        }
+    }
 
-            run_scenario = !self.computer.is_finished();
+    pub fn play_with(
+        &mut self,
+        strategy: &mut dyn JoystickStrategy,
+        mut renderer: Option<&mut dyn Renderer>,
+    ) -> Result<GameResult, String> {
+        while self.run_until_input()? {
+            if let Some(renderer) = renderer.as_deref_mut() {
+                renderer.render(&self.arcade, self.score);
+            }
+            let joystick = self.decide(strategy);
+            self.push_joystick(joystick);
         }
-        self.arcade.how_many_block()
+        Ok(self.result())
+    }
+
+    pub fn run_headless(&mut self) -> Result<GameResult, String> {
+        self.play(None)
+    }
+
+    pub fn execute_scenario(&mut self) -> Result<usize, String> {
+        self.play(Some(&mut TerminalRenderer::default()))
+            .map(|result| result.blocks_remaining)
     }
 
     pub fn print_grid(&self) {
@@ -160,6 +514,9 @@ impl Scenario {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ocr;
+    use parser::Parser;
+    use std::path::Path;
 
     #[test]
     fn test_from_integers() {
@@ -169,4 +526,148 @@ mod tests {
         arcade.print_grid();
         assert_eq!(arcade.how_many_block(), 0);
     }
+
+    // Draws two blocks, reads the joystick once, clears one block and
+    // reports a score before halting.
+    const TINY_GAME: [i64; 28] = [
+        104, 1, 104, 1, 104, 2, 104, 2, 104, 1, 104, 2, 3, 27, 104, 1, 104, 1, 104, 0, 104, -1,
+        104, 0, 104, 42, 99, 0,
+    ];
+
+    #[test]
+    fn test_headless_day13() {
+        let day13 = concat!(env!("CARGO_MANIFEST_DIR"), "/../day13/data/");
+        let mut memory = Parser::from_txt(Path::new(&format!("{}input.txt", day13)));
+        Parser::patch_from_txt(Path::new(&format!("{}free_play.patch", day13))).apply(&mut memory);
+        let result = Scenario::new(memory).run_headless().unwrap();
+        assert_eq!(result.status, GameStatus::Won);
+        assert_eq!(result.score, 13989);
+        assert_eq!(result.blocks_remaining, 0);
+    }
+
+    #[test]
+    fn test_headless_game() {
+        let program = TINY_GAME.to_vec();
+        let mut scenario = Scenario::new(program);
+        let result = scenario.run_headless().unwrap();
+        assert_eq!(result.score, 42);
+        assert_eq!(result.blocks_broken, 1);
+        assert_eq!(result.blocks_remaining, 1);
+        assert_eq!(result.frames, 1);
+        assert_eq!(result.status, GameStatus::Halted);
+        assert_eq!(result.missed_at, None);
+        assert!(result.steps > 0);
+    }
+
+    // Draws the paddle at (2, 3) and a block, then the ball on the paddle
+    // row at (4, 3) every frame until the game stops asking.
+    const MISSED_BALL: [i64; 27] = [
+        104, 2, 104, 3, 104, 3, 104, 0, 104, 0, 104, 2, 3, 26, 104, 4, 104, 3, 104, 4, 1105, 1, 12,
+        0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn test_missed_ball_ends_the_game() {
+        let mut scenario = Scenario::new(MISSED_BALL.to_vec());
+        let result = scenario.run_headless().unwrap();
+        assert_eq!(result.status, GameStatus::Lost);
+        assert_eq!(result.missed_at, Some(1));
+        assert_eq!(result.frames, 1);
+        assert_eq!(result.blocks_remaining, 1);
+        assert!(scenario.is_over());
+    }
+
+    // Draws the ball at (1, 1), then reads the joystick and halts.
+    const NO_PADDLE: [i64; 9] = [104, 1, 104, 1, 104, 4, 3, 20, 99];
+
+    #[test]
+    fn test_no_paddle_yet() {
+        let mut scenario = Scenario::new(NO_PADDLE.to_vec());
+        assert!(scenario.run_until_input().unwrap());
+        assert_eq!(scenario.paddle(), None);
+        assert_eq!(scenario.decide(&mut FollowBall), 0);
+        assert!(!scenario.is_over());
+    }
+
+    // Reads the joystick forever and reports each position as the score.
+    const ECHO_JOYSTICK: [i64; 11] = [3, 100, 104, -1, 104, 0, 4, 100, 1105, 1, 0];
+
+    #[test]
+    fn test_save_states_and_rewind() {
+        let mut scenario = Scenario::new(ECHO_JOYSTICK.to_vec());
+        scenario.enable_rewind(3);
+        assert!(scenario.run_until_input().unwrap());
+        scenario.quick_save();
+        for joystick in [1, -1, 0, 1] {
+            scenario.push_joystick(joystick);
+            assert!(scenario.run_until_input().unwrap());
+        }
+        assert_eq!((scenario.frames, scenario.score), (5, 1));
+        assert_eq!(scenario.rewind_points(), 3);
+        assert!(!scenario.rewind(3));
+
+        assert!(scenario.rewind(2));
+        assert_eq!((scenario.frames, scenario.score), (3, -1));
+        assert_eq!(scenario.rewind_points(), 1);
+        // Loading waits for the joystick again instead of starting a frame.
+        assert!(scenario.run_until_input().unwrap());
+        assert_eq!(scenario.frames, 3);
+        scenario.push_joystick(-1);
+        scenario.run_until_input().unwrap();
+        assert_eq!((scenario.frames, scenario.score), (4, -1));
+
+        assert!(scenario.quick_load());
+        assert_eq!((scenario.frames, scenario.score), (1, 0));
+        scenario.push_joystick(1);
+        scenario.run_until_input().unwrap();
+        assert_eq!(scenario.score, 1);
+    }
+
+    #[test]
+    fn test_read_block_letters() {
+        let mut arcade = Arcade::default();
+        let letter = ["#..#", "#..#", "####", "#..#", "#..#", "#..#"];
+        for (y, row) in letter.iter().enumerate() {
+            arcade.set_tile_at(0, y as i64 + 1, 1).unwrap();
+            for (x, cell) in row.chars().enumerate() {
+                let tile = if cell == '#' { 2 } else { 0 };
+                arcade
+                    .set_tile_at(x as i64 + 2, y as i64 + 1, tile)
+                    .unwrap();
+            }
+        }
+        let blocks = arcade.mask(|tile| tile == Tile::Block);
+        assert_eq!(ocr::read(&blocks), Ok(String::from("H")));
+        let rendered = ocr::grid_from_text(&arcade.render(), '#');
+        assert_eq!(ocr::read(&rendered), Ok(String::from("H")));
+    }
+
+    #[test]
+    fn test_screen_grows() {
+        let mut arcade = Arcade::default();
+        assert_eq!(arcade.tile_at(0, 0), None);
+        arcade.set_tile_at(50, 30, 2).unwrap();
+        arcade.set_tile_at(2, 1, 1).unwrap();
+        assert_eq!((arcade.width(), arcade.height()), (51, 31));
+        assert_eq!(arcade.tile_at(50, 30), Some(Tile::Block));
+        assert_eq!(arcade.tile_at(50, 0), Some(Tile::Empty));
+        assert!(arcade.render().lines().all(|line| line.len() == 51));
+
+        assert!(arcade.set_tile_at(-2, 0, 1).is_err());
+        assert!(arcade.set_tile_at(0, i64::MAX, 1).is_err());
+        assert_eq!(
+            arcade.set_tile_at(0, 0, 7).unwrap_err(),
+            "Unknown tile id 7"
+        );
+        assert_eq!(arcade.how_many_block(), 1);
+    }
+
+    #[test]
+    fn test_unknown_tile_stops_the_game() {
+        let mut scenario = Scenario::new(vec![104, 1, 104, 1, 104, 9, 99]);
+        assert_eq!(
+            scenario.run_headless().unwrap_err(),
+            "Step 3: Unknown tile id 9"
+        );
+    }
 }
@@ -0,0 +1,160 @@
+use crate::Computer;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub value: Option<i64>,
+}
+
+impl AsciiOutput {
+    fn push(&mut self, code: i64) {
+        if is_ascii_code(code) {
+            self.text.push(code as u8 as char);
+        } else {
+            self.value = Some(code);
+        }
+    }
+}
+
+pub fn is_ascii_code(value: i64) -> bool {
+    (0..=127).contains(&value)
+}
+
+pub fn to_codes(text: &str) -> Vec<i64> {
+    text.replace("\r\n", "\n").bytes().map(i64::from).collect()
+}
+
+#[derive(Default)]
+pub struct AsciiComputer {
+    computer: Computer,
+}
+
+impl From<Computer> for AsciiComputer {
+    fn from(computer: Computer) -> Self {
+        AsciiComputer { computer }
+    }
+}
+
+impl From<Vec<i64>> for AsciiComputer {
+    fn from(memory: Vec<i64>) -> Self {
+        AsciiComputer::from(Computer::new(memory))
+    }
+}
+
+impl AsciiComputer {
+    pub fn computer(&mut self) -> &mut Computer {
+        &mut self.computer
+    }
+
+    pub fn send(&mut self, text: &str) {
+        self.computer.push_inputs(to_codes(text));
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        self.send(line);
+        if !line.ends_with('\n') {
+            self.computer.push_input(10);
+        }
+    }
+
+    fn read_while(
+        &mut self,
+        mut keep_reading: impl FnMut(&AsciiOutput) -> bool,
+    ) -> Result<AsciiOutput, String> {
+        let mut output = AsciiOutput::default();
+        while keep_reading(&output) {
+            match self.computer.process_queued()? {
+                Some(code) => output.push(code),
+                None => break,
+            }
+        }
+        Ok(output)
+    }
+
+    pub fn read_line(&mut self) -> Result<AsciiOutput, String> {
+        self.read_while(|output| !output.text.ends_with('\n'))
+    }
+
+    pub fn read_until(&mut self, prompt: &str) -> Result<AsciiOutput, String> {
+        self.read_while(|output| !output.text.ends_with(prompt))
+    }
+
+    pub fn collect_output(&mut self) -> Result<AsciiOutput, String> {
+        self.read_while(|_| true)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.computer.is_finished()
+    }
+
+    pub fn is_waiting_input(&self) -> bool {
+        self.computer.is_waiting_input()
+    }
+
+    pub fn run_terminal(
+        &mut self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+    ) -> io::Result<Option<i64>> {
+        let mut value = None;
+        loop {
+            let screen = self.collect_output().map_err(io::Error::other)?;
+            write!(output, "{}", screen.text)?;
+            output.flush()?;
+            value = screen.value.or(value);
+            if !self.is_waiting_input() {
+                return Ok(value);
+            }
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(value);
+            }
+            self.send_line(&line);
+        }
+    }
+
+    pub fn run_interactive(&mut self) -> io::Result<Option<i64>> {
+        self.run_terminal(io::stdin().lock(), io::stdout())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Echoes every input code until it echoes a newline, then halts.
+    const ECHO: [i64; 12] = [3, 100, 4, 100, 1008, 100, 10, 101, 1006, 101, 0, 99];
+
+    #[test]
+    fn test_collect_output_with_answer() {
+        let memory = vec![104, 72, 104, 105, 104, 10, 104, 1234, 99];
+        let mut ascii = AsciiComputer::from(memory);
+        let output = ascii.collect_output().unwrap();
+        assert_eq!(output.text, "Hi\n");
+        assert_eq!(output.value, Some(1234));
+        assert!(ascii.is_finished());
+    }
+
+    #[test]
+    fn test_echo_line() {
+        let mut ascii = AsciiComputer::from(ECHO.to_vec());
+        assert_eq!(ascii.read_line().unwrap().text, "");
+        assert!(ascii.is_waiting_input());
+        ascii.send_line("WALK\r\n");
+        assert_eq!(ascii.read_line().unwrap().text, "WALK\n");
+        assert!(ascii.read_line().unwrap().text.is_empty());
+        assert!(ascii.is_finished());
+    }
+
+    #[test]
+    fn test_terminal() {
+        let mut ascii = AsciiComputer::from(ECHO.to_vec());
+        let mut screen = Vec::new();
+        let value = ascii
+            .run_terminal("north\n".as_bytes(), &mut screen)
+            .unwrap();
+        assert_eq!(value, None);
+        assert_eq!(String::from_utf8(screen).unwrap(), "north\n");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::Computer;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitStatus {
+    Halted,
+    Blocked,
+}
+
+struct Node {
+    computer: Computer,
+    outputs: Vec<i64>,
+    halted: bool,
+}
+
+#[derive(Default)]
+pub struct CircuitBuilder {
+    nodes: Vec<(Vec<i64>, Vec<i64>)>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl CircuitBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, program: Vec<i64>, initial_inputs: Vec<i64>) -> usize {
+        self.nodes.push((program, initial_inputs));
+        self.nodes.len() - 1
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize) -> &mut Self {
+        self.edges.push((from, to));
+        self
+    }
+
+    pub fn build(self) -> Result<Circuit, String> {
+        if let Some((from, to)) = self
+            .edges
+            .iter()
+            .find(|(from, to)| *from >= self.nodes.len() || *to >= self.nodes.len())
+        {
+            return Err(format!("Edge {} -> {} points to a missing node", from, to));
+        }
+        let nodes = self
+            .nodes
+            .into_iter()
+            .map(|(program, initial_inputs)| {
+                let mut computer = Computer::new(program);
+                computer.push_inputs(initial_inputs);
+                Node {
+                    computer,
+                    outputs: Vec::new(),
+                    halted: false,
+                }
+            })
+            .collect();
+        Ok(Circuit {
+            nodes,
+            edges: self.edges,
+        })
+    }
+}
+
+// A set of computers where every output of a node is copied to the input of
+// each node it is connected to. Outputs are also kept per node.
+pub struct Circuit {
+    nodes: Vec<Node>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Circuit {
+    // The day 7 feedback loop: node i gets phases[i], the first node also
+    // gets the 0 signal, and the last node feeds back into the first.
+    pub fn ring(program: &[i64], phases: &[i64]) -> Circuit {
+        let mut builder = CircuitBuilder::new();
+        for (i, phase) in phases.iter().enumerate() {
+            let inputs = if i == 0 {
+                vec![*phase, 0]
+            } else {
+                vec![*phase]
+            };
+            builder.add_node(program.to_vec(), inputs);
+        }
+        for i in 0..phases.len() {
+            builder.connect(i, (i + 1) % phases.len());
+        }
+        builder.build().unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn push_input(&mut self, node: usize, value: i64) {
+        self.nodes[node].computer.push_input(value);
+    }
+
+    pub fn outputs(&self, node: usize) -> &[i64] {
+        &self.nodes[node].outputs
+    }
+
+    pub fn last_output(&self, node: usize) -> Option<i64> {
+        self.nodes[node].outputs.last().copied()
+    }
+
+    pub fn is_halted(&self, node: usize) -> bool {
+        self.nodes[node].halted
+    }
+
+    // Runs the nodes in turn until all of them halt, or until a whole round
+    // goes by without any output or halt, which means they all wait for input.
+    pub fn run(&mut self) -> Result<CircuitStatus, String> {
+        loop {
+            let mut progress = false;
+            for i in 0..self.nodes.len() {
+                if self.nodes[i].halted {
+                    continue;
+                }
+                while let Some(value) = self.nodes[i].computer.process_queued()? {
+                    self.nodes[i].outputs.push(value);
+                    for (_, to) in self.edges.iter().filter(|(from, _)| *from == i) {
+                        self.nodes[*to].computer.push_input(value);
+                    }
+                    progress = true;
+                }
+                if self.nodes[i].computer.is_finished() {
+                    self.nodes[i].halted = true;
+                    progress = true;
+                }
+            }
+            if self.nodes.iter().all(|node| node.halted) {
+                return Ok(CircuitStatus::Halted);
+            }
+            if !progress {
+                return Ok(CircuitStatus::Blocked);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amplifier::FEEDBACK_EXAMPLE;
+
+    // Reads a value and outputs it doubled.
+    const DOUBLE: [i64; 10] = [3, 9, 102, 2, 9, 9, 4, 9, 99, 0];
+
+    #[test]
+    fn test_feedback_ring() {
+        let mut circuit = Circuit::ring(&FEEDBACK_EXAMPLE, &[9, 8, 7, 6, 5]);
+        assert_eq!(circuit.run().unwrap(), CircuitStatus::Halted);
+        assert_eq!(circuit.last_output(4), Some(139629729));
+
+        let mut circuit = Circuit::ring(&FEEDBACK_EXAMPLE, &[9, 8, 7]);
+        circuit.run().unwrap();
+        assert_eq!(circuit.last_output(2), Some(145111));
+    }
+
+    #[test]
+    fn test_fan_out_pipeline() {
+        let mut builder = CircuitBuilder::new();
+        let source = builder.add_node(DOUBLE.to_vec(), vec![5]);
+        let left = builder.add_node(DOUBLE.to_vec(), vec![]);
+        let right = builder.add_node(DOUBLE.to_vec(), vec![]);
+        builder.connect(source, left).connect(source, right);
+        let mut circuit = builder.build().unwrap();
+        assert_eq!(circuit.run().unwrap(), CircuitStatus::Halted);
+        assert_eq!(circuit.outputs(source), &[10]);
+        assert_eq!(circuit.outputs(left), &[20]);
+        assert_eq!(circuit.outputs(right), &[20]);
+    }
+
+    #[test]
+    fn test_blocked_and_invalid() {
+        let mut builder = CircuitBuilder::new();
+        builder.add_node(DOUBLE.to_vec(), vec![]);
+        let mut circuit = builder.build().unwrap();
+        assert_eq!(circuit.run().unwrap(), CircuitStatus::Blocked);
+        circuit.push_input(0, 4);
+        assert_eq!(circuit.run().unwrap(), CircuitStatus::Halted);
+        assert_eq!(circuit.last_output(0), Some(8));
+
+        let mut builder = CircuitBuilder::new();
+        builder.add_node(DOUBLE.to_vec(), vec![]);
+        builder.connect(0, 3);
+        assert!(builder.build().is_err());
+    }
+}
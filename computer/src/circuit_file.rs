@@ -0,0 +1,202 @@
+use crate::circuit::{Circuit, CircuitBuilder};
+use parser::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDescription {
+    pub name: String,
+    pub program: PathBuf,
+    pub inputs: Vec<i64>,
+    pub outputs: Vec<String>,
+}
+
+// One node per line, `#` starts a comment:
+//   A: program=input.txt inputs=9,0 -> B, C
+// Program paths are relative to the description file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CircuitDescription {
+    pub nodes: Vec<NodeDescription>,
+}
+
+fn parse_node(line: &str) -> Result<NodeDescription, String> {
+    let (name, rest) = line.split_once(':').ok_or("expected `name: ...`")?;
+    let (settings, outputs) = rest.split_once("->").unwrap_or((rest, ""));
+    let mut node = NodeDescription {
+        name: name.trim().to_string(),
+        program: PathBuf::new(),
+        inputs: Vec::new(),
+        outputs: outputs
+            .split(',')
+            .map(|output| output.trim().to_string())
+            .filter(|output| !output.is_empty())
+            .collect(),
+    };
+    for setting in settings.split_whitespace() {
+        match setting.split_once('=') {
+            Some(("program", path)) => node.program = PathBuf::from(path),
+            Some(("inputs", values)) => {
+                node.inputs = Parser::parse_str(values).map_err(|error| error.to_string())?
+            }
+            _ => return Err(format!("unknown setting {:?}", setting)),
+        }
+    }
+    if node.name.is_empty() {
+        return Err(String::from("node without a name"));
+    }
+    if node.program.as_os_str().is_empty() {
+        return Err(format!("node {} has no program", node.name));
+    }
+    Ok(node)
+}
+
+impl FromStr for CircuitDescription {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut description = CircuitDescription::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let node =
+                parse_node(line).map_err(|error| format!("line {}: {}", number + 1, error))?;
+            if description.index_of(&node.name).is_some() {
+                return Err(format!("line {}: duplicate node {}", number + 1, node.name));
+            }
+            description.nodes.push(node);
+        }
+        for node in &description.nodes {
+            if let Some(output) = node
+                .outputs
+                .iter()
+                .find(|output| description.index_of(output).is_none())
+            {
+                return Err(format!(
+                    "node {} is wired to unknown node {}",
+                    node.name, output
+                ));
+            }
+        }
+        Ok(description)
+    }
+}
+
+impl CircuitDescription {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        let mut description: CircuitDescription = text
+            .parse()
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for node in description.nodes.iter_mut() {
+            node.program = directory.join(&node.program);
+        }
+        Ok(description)
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    pub fn build(&self) -> Result<Circuit, String> {
+        let mut builder = CircuitBuilder::new();
+        for node in &self.nodes {
+            let program =
+                Parser::try_program_from_txt(&node.program).map_err(|error| error.to_string())?;
+            builder.add_node(program.memory, node.inputs.clone());
+        }
+        for (from, node) in self.nodes.iter().enumerate() {
+            for output in &node.outputs {
+                builder.connect(from, self.index_of(output).unwrap());
+            }
+        }
+        builder.build()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph circuit {\n");
+        for node in &self.nodes {
+            let inputs: Vec<String> = node.inputs.iter().map(|value| value.to_string()).collect();
+            dot += &format!(
+                "    \"{}\" [label=\"{}\\n{}\\ninputs: {}\"];\n",
+                node.name,
+                node.name,
+                node.program.display(),
+                inputs.join(",")
+            );
+        }
+        for node in &self.nodes {
+            for output in &node.outputs {
+                dot += &format!("    \"{}\" -> \"{}\";\n", node.name, output);
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amplifier::FEEDBACK_EXAMPLE;
+    use crate::circuit::CircuitStatus;
+    use std::env;
+    use std::process;
+
+    static FEEDBACK: &str = "# day 7 part 2 example
+A: program=example.txt inputs=9,0 -> B
+B: program=example.txt inputs=8 -> C
+C: program=example.txt inputs=7 -> D
+D: program=example.txt inputs=6 -> E
+E: program=example.txt inputs=5 -> A
+";
+
+    #[test]
+    fn test_run_description_file() {
+        let directory =
+            env::temp_dir().join(format!("test_run_description_file_{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("example.txt"),
+            FEEDBACK_EXAMPLE.map(|value| value.to_string()).join(","),
+        )
+        .unwrap();
+        fs::write(directory.join("feedback.circuit"), FEEDBACK).unwrap();
+
+        let description =
+            CircuitDescription::from_file(&directory.join("feedback.circuit")).unwrap();
+        let mut circuit = description.build().unwrap();
+        assert_eq!(circuit.run().unwrap(), CircuitStatus::Halted);
+        assert_eq!(
+            circuit.last_output(description.index_of("E").unwrap()),
+            Some(139629729)
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_dot_and_errors() {
+        let description: CircuitDescription =
+            "in: program=a.txt inputs=1 -> x, y\nx: program=a.txt\ny: program=b.txt"
+                .parse()
+                .unwrap();
+        let dot = description.to_dot();
+        assert!(dot.contains("\"in\" -> \"x\";\n    \"in\" -> \"y\";"));
+        assert!(dot.contains("[label=\"in\\na.txt\\ninputs: 1\"]"));
+
+        assert!(
+            "a: program=p.txt -> b"
+                .parse::<CircuitDescription>()
+                .is_err()
+        );
+        assert!("a: inputs=1".parse::<CircuitDescription>().is_err());
+        let error = "a: program=p\na: program=q"
+            .parse::<CircuitDescription>()
+            .unwrap_err();
+        assert_eq!(error, "line 2: duplicate node a");
+    }
+}
@@ -1,6 +1,9 @@
+use crate::disassembler::disassemble;
+use parser::{Patch, Program, SymbolTable};
+use std::collections::VecDeque;
 use std::io;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Computer {
     pub memory: Vec<i64>,
     aditional_memory: Vec<i64>,
@@ -12,14 +15,21 @@ pub struct Computer {
     status: Status,
     relative_base: i64,
     pub is_automatic_input: bool,
+    input_queue: VecDeque<i64>,
+    pause_if_input: bool,
+    symbols: SymbolTable,
+    steps: usize,
 }
 
+const ADDITIONAL_MEMORY_SIZE: usize = 1_000_000;
+
 #[derive(Default, Clone, Copy, PartialEq)]
 enum Status {
     #[default]
     Halt,
     Paused,
     Running,
+    WaitingInput,
 }
 
 #[derive(Debug)]
@@ -134,11 +144,19 @@ impl TryFrom<&[i64]> for Instruction {
     }
 }
 
+impl From<Program> for Computer {
+    fn from(program: Program) -> Self {
+        let mut computer = Computer::new(program.memory);
+        computer.set_symbols(program.symbols);
+        computer
+    }
+}
+
 impl Computer {
     pub fn new(memory: Vec<i64>) -> Self {
         Self {
             memory,
-            aditional_memory: vec![0; 1000000],
+            aditional_memory: Vec::new(),
             pointer: 0,
             input_instruction: 0,
             output: 0,
@@ -147,14 +165,39 @@ impl Computer {
             status: Status::Halt,
             relative_base: 0,
             is_automatic_input: true,
+            input_queue: VecDeque::new(),
+            pause_if_input: false,
+            symbols: SymbolTable::default(),
+            steps: 0,
         }
     }
 
+    pub fn set_symbols(&mut self, symbols: SymbolTable) {
+        self.symbols = symbols;
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    pub fn disassemble(&self) -> Vec<String> {
+        disassemble(&self.memory, &self.symbols)
+    }
+
     fn set_memory_at(&mut self, position: usize, value: i64) {
         if position < self.memory.len() {
             self.memory[position] = value;
         } else {
             let position: usize = position - self.memory.len();
+            // Grows on demand so computers stay cheap to clone.
+            if position >= self.aditional_memory.len() {
+                assert!(
+                    position < ADDITIONAL_MEMORY_SIZE,
+                    "Address {} is out of memory",
+                    position + self.memory.len()
+                );
+                self.aditional_memory.resize(position + 1, 0);
+            }
             self.aditional_memory[position] = value;
         }
     }
@@ -164,7 +207,7 @@ impl Computer {
             self.memory[position]
         } else {
             let position = position - self.memory.len();
-            self.aditional_memory[position]
+            self.aditional_memory.get(position).copied().unwrap_or(0)
         }
     }
 
@@ -172,10 +215,21 @@ impl Computer {
         &self.memory[start..end]
     }
 
+    pub fn apply_patch(&mut self, patch: &Patch) {
+        for entry in &patch.entries {
+            self.set_memory_at(entry.address, entry.value);
+        }
+    }
+
     pub fn set_phase_setting(&mut self, phase_setting: i64) {
         self.phase_setting = phase_setting;
         self.phase_set = true;
     }
+
+    pub fn take_phase_setting(&mut self) -> Option<i64> {
+        let phase_set = std::mem::take(&mut self.phase_set);
+        phase_set.then_some(self.phase_setting)
+    }
     fn get_value(&self, modedvalue: ModedValue) -> i64 {
         match modedvalue {
             ModedValue::Position(is_write, pos) => {
@@ -216,6 +270,11 @@ impl Computer {
             Instruction::Input(a) => {
                 let input_value = if self.pointer == 0 && self.phase_set {
                     self.phase_setting
+                } else if let Some(value) = self.input_queue.pop_front() {
+                    value
+                } else if self.pause_if_input {
+                    self.status = Status::WaitingInput;
+                    return Ok(None);
                 } else if self.is_automatic_input {
                     self.input_instruction
                 } else {
@@ -272,18 +331,41 @@ impl Computer {
             }
         }
         self.pointer = next_pointer;
+        self.steps += 1;
         Ok(output)
     }
 
-    pub fn process(&mut self, input_instruction: i64, pause_if_output: bool) -> bool {
+    pub fn process(
+        &mut self,
+        input_instruction: i64,
+        pause_if_output: bool,
+    ) -> Result<bool, String> {
         self.input_instruction = input_instruction;
+        self.pause_if_input = false;
+        self.run(pause_if_output)?;
+        Ok(self.status == Status::Halt)
+        //println!("Processed");
+    }
+
+    // Runs until the next output, returning it, or until the program halts or
+    // needs a value that is not in the input queue, returning None.
+    pub fn process_queued(&mut self) -> Result<Option<i64>, String> {
+        self.pause_if_input = true;
+        self.run(true)?;
+        if self.status == Status::Paused {
+            Ok(Some(self.output))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn run(&mut self, pause_if_output: bool) -> Result<(), String> {
         self.status = Status::Running;
         while self.status == Status::Running {
-            let result = self.execute_next_instruction();
-            if let Err(a) = &result {
-                println!("{} {}", a, self.pointer);
-            }
-            if let Some(result_value) = &result.unwrap().clone() {
+            let result = self.execute_next_instruction().map_err(|error| {
+                format!("{} {}", error, self.symbols.format_address(self.pointer))
+            })?;
+            if let Some(result_value) = &result {
                 self.output = *result_value;
                 //println!("{}", self.output);
                 if pause_if_output {
@@ -291,8 +373,19 @@ impl Computer {
                 }
             }
         }
-        self.status == Status::Halt
-        //println!("Processed");
+        Ok(())
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.input_queue.push_back(value);
+    }
+
+    pub fn push_inputs(&mut self, values: impl IntoIterator<Item = i64>) {
+        self.input_queue.extend(values);
+    }
+
+    pub fn is_waiting_input(&self) -> bool {
+        self.status == Status::WaitingInput
     }
 
     pub fn get_first_position(&self) -> i64 {
@@ -306,6 +399,10 @@ impl Computer {
     pub fn get_output(&self) -> i64 {
         self.output
     }
+
+    pub fn get_steps(&self) -> usize {
+        self.steps
+    }
 }
 
 #[cfg(test)]
@@ -316,7 +413,7 @@ mod tests {
     fn test_from_integers() {
         let memory = vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
         let mut code = Computer::new(memory);
-        code.process(0, false);
+        code.process(0, false).unwrap();
         assert_eq!(code.get_first_position(), 3500);
     }
 
@@ -324,7 +421,7 @@ mod tests {
     fn test_from_integers2() {
         let memory = vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
         let mut code = Computer::new(memory);
-        code.process(1, false);
+        code.process(1, false).unwrap();
     }
 
     #[test]
@@ -333,7 +430,40 @@ mod tests {
             109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
         ];
         let mut code = Computer::new(memory);
-        code.process(1, false);
+        code.process(1, false).unwrap();
+    }
+
+    #[test]
+    fn test_queued_input() {
+        let memory = vec![3, 13, 3, 14, 1, 13, 14, 15, 104, 7, 4, 15, 99, 0, 0, 0];
+        let mut code = Computer::new(memory);
+        code.push_input(2);
+        assert_eq!(code.process_queued(), Ok(None));
+        assert!(code.is_waiting_input());
+        code.push_input(3);
+        assert_eq!(code.process_queued(), Ok(Some(7)));
+        assert_eq!(code.process_queued(), Ok(Some(5)));
+        assert_eq!(code.process_queued(), Ok(None));
+        assert!(code.is_finished());
+    }
+
+    #[test]
+    fn test_invalid_instruction() {
+        let program: Program = "1101, 2, 3, 7, @bad 42".parse().unwrap();
+        let mut code = Computer::from(program);
+        assert_eq!(
+            code.process_queued(),
+            Err(String::from("ERROR IN Instruction @bad"))
+        );
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let memory = vec![1, 0, 0, 0, 99, 5, 6];
+        let mut code = Computer::new(memory);
+        code.apply_patch(&"1=5\n2=6".parse().unwrap());
+        code.process(0, false).unwrap();
+        assert_eq!(code.get_first_position(), 11);
     }
 
     #[test]
@@ -989,17 +1119,17 @@ mod tests {
             0,
         ];
         let mut code = Computer::new(memory);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
-        code.process(0, true);
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
+        code.process(0, true).unwrap();
     }
 }
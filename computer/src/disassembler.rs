@@ -0,0 +1,92 @@
+use parser::SymbolTable;
+
+fn mnemonic(opcode: i64) -> Option<(&'static str, usize)> {
+    match opcode % 100 {
+        1 => Some(("ADD", 3)),
+        2 => Some(("MUL", 3)),
+        3 => Some(("IN", 1)),
+        4 => Some(("OUT", 1)),
+        5 => Some(("JNZ", 2)),
+        6 => Some(("JZ", 2)),
+        7 => Some(("LT", 3)),
+        8 => Some(("EQ", 3)),
+        9 => Some(("ARB", 1)),
+        99 => Some(("HALT", 0)),
+        _ => None,
+    }
+}
+
+fn format_parameter(mode: i64, value: i64, is_jump: bool, symbols: &SymbolTable) -> Option<String> {
+    match mode {
+        0 if value >= 0 => Some(symbols.format_address(value as usize)),
+        0 => Some(format!("[{}]", value)),
+        1 if is_jump && value >= 0 => Some(
+            symbols
+                .name_at(value as usize)
+                .map_or(value.to_string(), |name| format!("@{}", name)),
+        ),
+        1 => Some(value.to_string()),
+        2 => Some(format!("[rb{:+}]", value)),
+        _ => None,
+    }
+}
+
+// Decodes the instruction at `address`, returning its text and length.
+// Anything that is not a valid instruction is shown as a single DATA word.
+pub fn disassemble_at(memory: &[i64], address: usize, symbols: &SymbolTable) -> (String, usize) {
+    let opcode = memory[address];
+    let data = (format!("DATA {}", opcode), 1);
+    let Some((name, parameters)) = mnemonic(opcode).filter(|_| opcode >= 0) else {
+        return data;
+    };
+    if address + parameters >= memory.len() {
+        return data;
+    }
+    let mut operands = Vec::new();
+    for i in 0..parameters {
+        let mode = (opcode / 10_i64.pow(i as u32 + 2)) % 10;
+        let is_jump = (name == "JNZ" || name == "JZ") && i == 1;
+        match format_parameter(mode, memory[address + 1 + i], is_jump, symbols) {
+            Some(operand) => operands.push(operand),
+            None => return data,
+        }
+    }
+    (
+        format!("{} {}", name, operands.join(", "))
+            .trim_end()
+            .to_string(),
+        parameters + 1,
+    )
+}
+
+pub fn disassemble(memory: &[i64], symbols: &SymbolTable) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < memory.len() {
+        let (text, length) = disassemble_at(memory, address, symbols);
+        let label = symbols
+            .name_at(address)
+            .map_or(String::new(), |name| format!("@{}", name));
+        lines.push(format!("{:>5}  {:<12} {}", address, label, text));
+        address += length;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::Program;
+
+    #[test]
+    fn test_disassemble_with_symbols() {
+        let program: Program = "1,9,10,3, @loop 1005,11,4, 99, 30,40,50, @score 7"
+            .parse()
+            .unwrap();
+        let lines = disassemble(&program.memory, &program.symbols);
+        assert_eq!(lines[0], "    0               ADD [9], [10], [3]");
+        assert_eq!(lines[1], "    4  @loop        JNZ @score, @loop");
+        assert_eq!(lines[2], "    7               HALT");
+        assert_eq!(lines[3], "    8               DATA 30");
+    }
+}
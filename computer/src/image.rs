@@ -0,0 +1,219 @@
+use crate::arcade::Arcade;
+use crate::recording::{Recording, Replay};
+use std::fs;
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+// Colour for each tile id or panel colour; ids without an entry get the
+// default colour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<(i64, Rgb)>,
+    default: Rgb,
+}
+
+impl Palette {
+    pub fn new(default: Rgb) -> Self {
+        Palette {
+            colors: Vec::new(),
+            default,
+        }
+    }
+
+    pub fn with(mut self, id: i64, color: Rgb) -> Self {
+        self.colors.retain(|(known, _)| *known != id);
+        self.colors.push((id, color));
+        self
+    }
+
+    pub fn color(&self, id: i64) -> Rgb {
+        self.colors
+            .iter()
+            .find(|(known, _)| *known == id)
+            .map_or(self.default, |(_, color)| *color)
+    }
+
+    pub fn arcade() -> Self {
+        Palette::new([0, 0, 0])
+            .with(1, [128, 128, 128])
+            .with(2, [200, 60, 40])
+            .with(3, [240, 240, 240])
+            .with(4, [250, 200, 0])
+    }
+
+    pub fn hull() -> Self {
+        Palette::new([128, 128, 128])
+            .with(0, [0, 0, 0])
+            .with(1, [255, 255, 255])
+    }
+}
+
+// Written as plain (ASCII) PPM and PBM so images can be diffed and kept as
+// golden files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    // Every grid cell becomes a `scale` × `scale` square.
+    pub fn from_grid(
+        width: usize,
+        height: usize,
+        id_at: impl Fn(usize, usize) -> i64,
+        palette: &Palette,
+        scale: usize,
+    ) -> Self {
+        let scale = scale.max(1);
+        let mut pixels = Vec::with_capacity(width * height * scale * scale);
+        for y in 0..height * scale {
+            for x in 0..width * scale {
+                pixels.push(palette.color(id_at(x / scale, y / scale)));
+            }
+        }
+        Image {
+            width: width * scale,
+            height: height * scale,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    pub fn to_ppm(&self) -> String {
+        let mut ppm = format!("P3\n{} {}\n255\n", self.width, self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let values: Vec<String> = row
+                .iter()
+                .map(|[r, g, b]| format!("{} {} {}", r, g, b))
+                .collect();
+            ppm += &values.join(" ");
+            ppm.push('\n');
+        }
+        ppm
+    }
+
+    // Dark pixels are black (1) and light ones white (0).
+    pub fn to_pbm(&self) -> String {
+        let mut pbm = format!("P1\n{} {}\n", self.width, self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let bits: Vec<&str> = row
+                .iter()
+                .map(|[r, g, b]| {
+                    let luma = (*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000;
+                    if luma < 128 { "1" } else { "0" }
+                })
+                .collect();
+            pbm += &bits.join(" ");
+            pbm.push('\n');
+        }
+        pbm
+    }
+
+    // The format follows the extension: `.pbm` or anything else for PPM.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("pbm") => self.to_pbm(),
+            _ => self.to_ppm(),
+        };
+        fs::write(path, contents).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+}
+
+pub fn arcade_image(arcade: &Arcade, palette: &Palette, scale: usize) -> Image {
+    Image::from_grid(
+        arcade.width(),
+        arcade.height(),
+        |x, y| {
+            arcade
+                .tile_at(x as i64, y as i64)
+                .map_or(0, |tile| tile as i64)
+        },
+        palette,
+        scale,
+    )
+}
+
+// Writes frame_00000.<extension>, frame_00001.<extension>, ... into the
+// directory and returns how many frames were written.
+pub fn export_frames(
+    recording: Recording,
+    directory: &Path,
+    extension: &str,
+    palette: &Palette,
+    scale: usize,
+) -> Result<usize, String> {
+    fs::create_dir_all(directory).map_err(|error| format!("{}: {}", directory.display(), error))?;
+    let mut replay = Replay::new(recording)?;
+    for frame in 0..replay.frames() {
+        replay.seek(frame)?;
+        let path = directory.join(format!("frame_{:05}.{}", frame, extension));
+        arcade_image(replay.arcade(), palette, scale).save(&path)?;
+    }
+    Ok(replay.frames())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn small_arcade() -> Arcade {
+        let mut arcade = Arcade::default();
+        arcade.set_tile_at(0, 0, 1).unwrap();
+        arcade.set_tile_at(1, 0, 2).unwrap();
+        arcade.set_tile_at(1, 1, 4).unwrap();
+        arcade
+    }
+
+    #[test]
+    fn test_golden_ppm() {
+        let palette = Palette::new([0, 0, 0])
+            .with(1, [9, 9, 9])
+            .with(2, [255, 0, 0])
+            .with(4, [255, 255, 255]);
+        let image = arcade_image(&small_arcade(), &palette, 2);
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(
+            image.to_ppm(),
+            "P3\n4 4\n255\n\
+             9 9 9 9 9 9 255 0 0 255 0 0\n\
+             9 9 9 9 9 9 255 0 0 255 0 0\n\
+             0 0 0 0 0 0 255 255 255 255 255 255\n\
+             0 0 0 0 0 0 255 255 255 255 255 255\n"
+        );
+        assert_eq!(
+            image.to_pbm(),
+            "P1\n4 4\n1 1 1 1\n1 1 1 1\n1 1 0 0\n1 1 0 0\n"
+        );
+    }
+
+    #[test]
+    fn test_export_frames() {
+        let recording: Recording = "3 0 tile 0 0 2\n6 1 joystick 0\n9 1 tile 1 0 4\n"
+            .parse()
+            .unwrap();
+        let directory = env::temp_dir().join(format!("test_export_frames_{}", process::id()));
+        let frames = export_frames(recording, &directory, "pbm", &Palette::arcade(), 1).unwrap();
+        assert_eq!(frames, 2);
+        let first = fs::read_to_string(directory.join("frame_00000.pbm")).unwrap();
+        let second = fs::read_to_string(directory.join("frame_00001.pbm")).unwrap();
+        assert_eq!(first, "P1\n1 1\n1\n");
+        assert_eq!(second, "P1\n2 1\n1 0\n");
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+use crate::arcade::{Arcade, GameResult, Scenario, Tile};
+use std::io::BufRead;
+
+// Decides the joystick position for the next frame: -1 left, 0 neutral,
+// 1 right. Ball and paddle are (x, y) positions on the screen.
+pub trait JoystickStrategy {
+    fn decide(&mut self, arcade: &Arcade, ball: (i64, i64), paddle: (i64, i64)) -> i64;
+}
+
+// Never moves; a baseline that loses the first ball it cannot reach.
+pub struct Hold;
+
+impl JoystickStrategy for Hold {
+    fn decide(&mut self, _arcade: &Arcade, _ball: (i64, i64), _paddle: (i64, i64)) -> i64 {
+        0
+    }
+}
+
+pub struct FollowBall;
+
+impl JoystickStrategy for FollowBall {
+    fn decide(&mut self, _arcade: &Arcade, ball: (i64, i64), paddle: (i64, i64)) -> i64 {
+        (ball.0 - paddle.0).signum()
+    }
+}
+
+// Works out the ball direction from its last two positions and, while the
+// ball falls, moves the paddle to where it will reach the paddle row. Only
+// walls are taken into account, so a rising ball is just followed.
+#[derive(Default)]
+pub struct PredictTrajectory {
+    last_ball: Option<(i64, i64)>,
+}
+
+const MAX_PREDICTION_STEPS: usize = 10_000;
+
+impl PredictTrajectory {
+    fn landing_x(arcade: &Arcade, ball: (i64, i64), speed: (i64, i64), paddle_y: i64) -> i64 {
+        let is_wall = |x, y| arcade.tile_at(x, y) == Some(Tile::Wall);
+        let (mut x, mut y) = ball;
+        let (mut dx, mut dy) = speed;
+        for _ in 0..MAX_PREDICTION_STEPS {
+            if dy > 0 && y + 1 >= paddle_y {
+                return x;
+            }
+            if is_wall(x + dx, y) {
+                dx = -dx;
+            }
+            if is_wall(x, y + dy) {
+                dy = -dy;
+            }
+            x += dx;
+            y += dy;
+        }
+        x
+    }
+}
+
+impl JoystickStrategy for PredictTrajectory {
+    fn decide(&mut self, arcade: &Arcade, ball: (i64, i64), paddle: (i64, i64)) -> i64 {
+        let target = match self.last_ball.replace(ball) {
+            Some(last) if ball.1 > last.1 => {
+                let speed = ((ball.0 - last.0).signum(), (ball.1 - last.1).signum());
+                Self::landing_x(arcade, ball, speed, paddle.1)
+            }
+            _ => ball.0,
+        };
+        (target - paddle.0).signum()
+    }
+}
+
+// Reads one line per frame: `a`/`h` moves left, `d`/`l` moves right and
+// anything else keeps the joystick neutral.
+pub struct Keyboard<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> Keyboard<R> {
+    pub fn new(reader: R) -> Self {
+        Keyboard { reader }
+    }
+}
+
+impl<R: BufRead> JoystickStrategy for Keyboard<R> {
+    fn decide(&mut self, _arcade: &Arcade, _ball: (i64, i64), _paddle: (i64, i64)) -> i64 {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).is_err() {
+            return 0;
+        }
+        match line.trim() {
+            "a" | "h" => -1,
+            "d" | "l" => 1,
+            _ => 0,
+        }
+    }
+}
+
+// Plays the same program once per strategy.
+pub fn compare(
+    memory: &[i64],
+    strategies: Vec<(String, Box<dyn JoystickStrategy>)>,
+) -> Result<Vec<(String, GameResult)>, String> {
+    strategies
+        .into_iter()
+        .map(|(name, mut strategy)| {
+            let mut scenario = Scenario::new(memory.to_vec());
+            Ok((name, scenario.play_with(strategy.as_mut(), None)?))
+        })
+        .collect()
+}
+
+pub fn comparison_table(results: &[(String, GameResult)]) -> String {
+    let mut table = format!(
+        "{:<12}  {:>8}  {:>10}  {:>8}  {:>7}  {:>6}  {:>6}\n",
+        "strategy", "score", "steps", "frames", "status", "blocks", "missed"
+    );
+    for (name, result) in results {
+        let missed = result
+            .missed_at
+            .map_or(String::from("-"), |frame| frame.to_string());
+        table += &format!(
+            "{:<12}  {:>8}  {:>10}  {:>8}  {:>7}  {:>6}  {:>6}\n",
+            name,
+            result.score,
+            result.steps,
+            result.frames,
+            format!("{:?}", result.status),
+            result.blocks_remaining,
+            missed
+        );
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predict_landing() {
+        let mut arcade = Arcade::default();
+        for y in 0..10 {
+            arcade.set_tile_at(0, y, 1).unwrap();
+            arcade.set_tile_at(6, y, 1).unwrap();
+        }
+        // Moving down and right from (3, 2), bouncing off the right wall.
+        let mut strategy = PredictTrajectory::default();
+        assert_eq!(strategy.decide(&arcade, (2, 1), (3, 9)), -1);
+        assert_eq!(strategy.decide(&arcade, (3, 2), (3, 9)), -1);
+        assert_eq!(PredictTrajectory::landing_x(&arcade, (3, 2), (1, 1), 9), 1);
+    }
+
+    #[test]
+    fn test_keyboard() {
+        let arcade = Arcade::default();
+        let mut keyboard = Keyboard::new("a\n\nd\nx\n".as_bytes());
+        let moves: Vec<i64> = (0..5)
+            .map(|_| keyboard.decide(&arcade, (0, 0), (0, 0)))
+            .collect();
+        assert_eq!(moves, vec![-1, 0, 1, 0, 0]);
+    }
+}
@@ -1,7 +1,17 @@
 pub mod amplifier;
+pub mod circuit;
+pub mod circuit_file;
 pub mod computer;
 pub mod arcade;
+pub mod joystick;
+pub mod recording;
+pub mod image;
+pub mod ocr;
+pub mod protocol;
 pub mod robot;
+pub mod trace;
+pub mod ascii;
+pub mod disassembler;
 pub use amplifier::Amplifier;
 pub use computer::Computer;
 pub use robot::Scenario;
@@ -0,0 +1,140 @@
+// Reads the block capitals some puzzles draw as their answer: 4 cells wide
+// (I is 3, Y is 5), 6 high, with at least one blank column between letters.
+pub const LETTER_WIDTH: usize = 4;
+pub const LETTER_HEIGHT: usize = 6;
+
+const LETTERS: [(char, [&str; LETTER_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    // Letters are matched from their first lit column, so narrow ones are
+    // stored padded on the right.
+    ('I', ["###.", ".#..", ".#..", ".#..", ".#..", "###."]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+// Rows of rendered text, where `ink` marks a lit cell.
+pub fn grid_from_text(text: &str, ink: char) -> Vec<Vec<bool>> {
+    text.lines()
+        .map(|line| line.chars().map(|cell| cell == ink).collect())
+        .collect()
+}
+
+fn bitmap(grid: &[Vec<bool>], rows: &[usize], columns: &[usize]) -> Vec<String> {
+    rows.iter()
+        .map(|y| {
+            columns
+                .iter()
+                .map(|x| match grid[*y].get(*x) {
+                    Some(true) => '#',
+                    _ => '.',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Blank rows around the text are ignored. Each run of lit columns is one
+// letter; a letter narrower than the font is padded on the right.
+pub fn read(grid: &[Vec<bool>]) -> Result<String, String> {
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    let is_lit = |x: usize, y: usize| grid[y].get(x).copied().unwrap_or(false);
+    let rows: Vec<usize> = (0..grid.len())
+        .filter(|y| (0..width).any(|x| is_lit(x, *y)))
+        .collect();
+    let (Some(top), Some(bottom)) = (rows.first(), rows.last()) else {
+        return Ok(String::new());
+    };
+    let rows: Vec<usize> = (*top..=*bottom).collect();
+    if rows.len() != LETTER_HEIGHT {
+        return Err(format!(
+            "Text is {} rows high instead of {}",
+            rows.len(),
+            LETTER_HEIGHT
+        ));
+    }
+
+    let mut letters = String::new();
+    let mut x = 0;
+    while x < width {
+        if !rows.iter().any(|y| is_lit(x, *y)) {
+            x += 1;
+            continue;
+        }
+        let left = x;
+        while x < width && rows.iter().any(|y| is_lit(x, *y)) {
+            x += 1;
+        }
+        let columns: Vec<usize> = (left..x.max(left + LETTER_WIDTH)).collect();
+        let glyph = bitmap(grid, &rows, &columns);
+        let letter = LETTERS
+            .iter()
+            .find(|(_, shape)| shape.iter().eq(glyph.iter()))
+            .map(|(letter, _)| *letter)
+            .ok_or_else(|| format!("Unknown glyph at column {}:\n{}", left, glyph.join("\n")))?;
+        letters.push(letter);
+    }
+    Ok(letters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_font() {
+        for (letter, shape) in LETTERS {
+            let grid = grid_from_text(&shape.join("\n"), '#');
+            assert_eq!(read(&grid), Ok(letter.to_string()));
+        }
+        let text = "\n\
+                    .###..####.####..##...##..#..#..##..####...\n\
+                    .#..#.#....#....#..#.#..#.#..#.#..#.#......\n\
+                    .###..###..###..#..#.#....####.#..#.###....\n\
+                    .#..#.#....#....####.#.##.#..#.####.#......\n\
+                    .#..#.#....#....#..#.#..#.#..#.#..#.#......\n\
+                    .###..#....####.#..#..###.#..#.#..#.#......\n";
+        assert_eq!(
+            read(&grid_from_text(text, '#')),
+            Ok(String::from("BFEAGHAF"))
+        );
+        assert_eq!(read(&[]), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_read_i() {
+        let text = ".###..#..#\n..#...#..#\n..#...####\n..#...#..#\n..#...#..#\n.###..#..#";
+        assert_eq!(read(&grid_from_text(text, '#')), Ok(String::from("IH")));
+    }
+
+    #[test]
+    fn test_read_y() {
+        let text = "#...#.#...\n#...#.#...\n.#.#..#...\n..#...#...\n..#...#...\n..#...####";
+        assert_eq!(read(&grid_from_text(text, '#')), Ok(String::from("YL")));
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let text = "#..#.#...#\n#..#.##.##\n####.#.#.#\n#..#.#...#\n#..#.#...#\n#..#.#...#";
+        assert_eq!(
+            read(&grid_from_text(text, '#')),
+            Err(String::from(
+                "Unknown glyph at column 5:\n#...#\n##.##\n#.#.#\n#...#\n#...#\n#...#"
+            ))
+        );
+        assert!(read(&grid_from_text("#\n#", '#')).is_err());
+    }
+}
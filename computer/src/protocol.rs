@@ -0,0 +1,94 @@
+use crate::robot::Direction;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Color {
+    #[default]
+    Black = 0,
+    White = 1,
+}
+
+impl TryFrom<i64> for Color {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Color::Black),
+            1 => Ok(Color::White),
+            _ => Err(format!("Unknown colour {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Movement {
+    TurnLeft,
+    TurnRight,
+    Face(Direction),
+}
+
+// How a painting program's second output tells the robot where to go next.
+// The first output is always a colour.
+pub trait Protocol {
+    fn movement(&self, code: i64) -> Result<Movement, String>;
+
+    fn decode(&self, color: i64, movement: i64) -> Result<(Color, Movement), String> {
+        Ok((Color::try_from(color)?, self.movement(movement)?))
+    }
+}
+
+// The day 11 command set: 0 turns left, 1 turns right.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Relative;
+
+impl Protocol for Relative {
+    fn movement(&self, code: i64) -> Result<Movement, String> {
+        match code {
+            0 => Ok(Movement::TurnLeft),
+            1 => Ok(Movement::TurnRight),
+            _ => Err(format!("Unknown turn code {}", code)),
+        }
+    }
+}
+
+// Headings clockwise from north: 0 north, 1 east, 2 south, 3 west.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AbsoluteHeading;
+
+impl Protocol for AbsoluteHeading {
+    fn movement(&self, code: i64) -> Result<Movement, String> {
+        let direction = match code {
+            0 => Direction::North,
+            1 => Direction::East,
+            2 => Direction::South,
+            3 => Direction::West,
+            _ => return Err(format!("Unknown heading {}", code)),
+        };
+        Ok(Movement::Face(direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            Relative.decode(1, 0),
+            Ok((Color::White, Movement::TurnLeft))
+        );
+        assert_eq!(
+            AbsoluteHeading.decode(0, 3),
+            Ok((Color::Black, Movement::Face(Direction::West)))
+        );
+        assert_eq!(Relative.decode(2, 0), Err(String::from("Unknown colour 2")));
+        assert_eq!(
+            Relative.decode(0, 2),
+            Err(String::from("Unknown turn code 2"))
+        );
+        assert_eq!(
+            AbsoluteHeading.decode(0, -1),
+            Err(String::from("Unknown heading -1"))
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::arcade::{Arcade, MAX_SCREEN_SIDE, Tile};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Tile { x: i64, y: i64, tile: Tile },
+    Score(i64),
+    Joystick(i64),
+}
+
+// `step` is the number of instructions run so far and `frame` the number of
+// times the game had read the joystick when the event happened.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordedEvent {
+    pub step: usize,
+    pub frame: usize,
+    pub event: Event,
+}
+
+// One event per line:
+//   <step> <frame> tile <x> <y> <id>
+//   <step> <frame> score <value>
+//   <step> <frame> joystick <value>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Recording {
+    events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    pub fn record(&mut self, step: usize, frame: usize, event: Event) {
+        self.events.push(RecordedEvent { step, frame, event });
+    }
+
+    pub fn truncate(&mut self, events: usize) {
+        self.events.truncate(events);
+    }
+
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    pub fn frames(&self) -> usize {
+        self.events.last().map_or(0, |event| event.frame + 1)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        text.parse()
+            .map_err(|error| format!("{}: {}", path.display(), error))
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for RecordedEvent { step, frame, event } in &self.events {
+            match event {
+                Event::Tile { x, y, tile } => {
+                    writeln!(f, "{} {} tile {} {} {}", step, frame, x, y, *tile as i64)?
+                }
+                Event::Score(score) => writeln!(f, "{} {} score {}", step, frame, score)?,
+                Event::Joystick(joystick) => {
+                    writeln!(f, "{} {} joystick {}", step, frame, joystick)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_event(line: &str) -> Result<RecordedEvent, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    fn number<T: FromStr>(fields: &[&str], index: usize) -> Result<T, String> {
+        let field = fields.get(index).ok_or("missing field")?;
+        field
+            .parse()
+            .map_err(|_| format!("invalid number {:?}", field))
+    }
+    let event = match (fields.get(2), fields.len()) {
+        (Some(&"tile"), 6) => {
+            let (x, y) = (number(&fields, 3)?, number(&fields, 4)?);
+            let on_screen = |value: i64| (0..MAX_SCREEN_SIDE as i64).contains(&value);
+            if !on_screen(x) || !on_screen(y) {
+                return Err(format!("position ({}, {}) is off screen", x, y));
+            }
+            let tile = Tile::try_from(number::<i64>(&fields, 5)?)?;
+            Event::Tile { x, y, tile }
+        }
+        (Some(&"score"), 4) => Event::Score(number(&fields, 3)?),
+        (Some(&"joystick"), 4) => Event::Joystick(number(&fields, 3)?),
+        _ => return Err(String::from("expected a tile, score or joystick event")),
+    };
+    // Steps and frames are counts, so negative values are rejected here.
+    Ok(RecordedEvent {
+        step: number(&fields, 0)?,
+        frame: number(&fields, 1)?,
+        event,
+    })
+}
+
+impl FromStr for Recording {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut recording = Recording::default();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event =
+                parse_event(line).map_err(|error| format!("line {}: {}", number + 1, error))?;
+            recording.events.push(event);
+        }
+        Ok(recording)
+    }
+}
+
+// Rebuilds the screen from a recording. Frame n shows everything drawn
+// before the game read the joystick for the (n + 1)th time.
+pub struct Replay {
+    recording: Recording,
+    arcade: Arcade,
+    score: i64,
+    frame: usize,
+    next_event: usize,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Result<Self, String> {
+        let mut replay = Replay {
+            recording,
+            arcade: Arcade::default(),
+            score: 0,
+            frame: 0,
+            next_event: 0,
+        };
+        replay.seek(0)?;
+        Ok(replay)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.recording.frames()
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn arcade(&self) -> &Arcade {
+        &self.arcade
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    // The joystick position chosen while looking at the current frame.
+    pub fn joystick(&self) -> Option<i64> {
+        self.recording.events[self.next_event..]
+            .iter()
+            .take_while(|event| event.frame <= self.frame + 1)
+            .find_map(|event| match event.event {
+                Event::Joystick(joystick) => Some(joystick),
+                _ => None,
+            })
+    }
+
+    // Fails on a tile the screen cannot hold, which only a recording built
+    // by hand can contain.
+    pub fn seek(&mut self, frame: usize) -> Result<(), String> {
+        let frame = frame.min(self.frames().saturating_sub(1));
+        if frame < self.frame || self.next_event == 0 {
+            self.arcade = Arcade::default();
+            self.score = 0;
+            self.next_event = 0;
+        }
+        while let Some(event) = self.recording.events.get(self.next_event) {
+            if event.frame > frame {
+                break;
+            }
+            match event.event {
+                Event::Tile { x, y, tile } => self.arcade.put_tile(x, y, tile)?,
+                Event::Score(score) => self.score = score,
+                Event::Joystick(_) => {}
+            }
+            self.next_event += 1;
+        }
+        self.frame = frame;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arcade::Scenario;
+
+    // Draws a block and the ball, reads the joystick, clears the block,
+    // scores and halts.
+    const TINY_GAME: [i64; 28] = [
+        104, 1, 104, 1, 104, 2, 104, 3, 104, 1, 104, 4, 3, 27, 104, 1, 104, 1, 104, 0, 104, -1,
+        104, 0, 104, 42, 99, 0,
+    ];
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut scenario = Scenario::new(TINY_GAME.to_vec());
+        scenario.start_recording();
+        scenario.run_headless().unwrap();
+        let recording = scenario.take_recording().unwrap();
+        assert_eq!(recording.events().len(), 5);
+        assert_eq!(recording.frames(), 2);
+
+        let text = recording.to_string();
+        assert_eq!(
+            text,
+            "3 0 tile 1 1 2\n6 0 tile 3 1 4\n6 1 joystick 0\n10 1 tile 1 1 0\n13 1 score 42\n"
+        );
+        let parsed: Recording = text.parse().unwrap();
+        assert_eq!(parsed, recording);
+
+        let mut replay = Replay::new(parsed).unwrap();
+        assert_eq!(replay.arcade().tile_at(1, 1), Some(Tile::Block));
+        assert_eq!(replay.joystick(), Some(0));
+        assert_eq!(replay.score(), 0);
+        replay.seek(1).unwrap();
+        assert_eq!(replay.arcade().tile_at(1, 1), Some(Tile::Empty));
+        assert_eq!(replay.score(), 42);
+        replay.seek(0).unwrap();
+        assert_eq!(replay.arcade().tile_at(1, 1), Some(Tile::Block));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "1 0 tile 1 1 2\n2 0 jump 3"
+                .parse::<Recording>()
+                .unwrap_err(),
+            "line 2: expected a tile, score or joystick event"
+        );
+        assert!("1 0 tile 1 1 7".parse::<Recording>().is_err());
+        assert_eq!(
+            "0 0 tile -5 0 2".parse::<Recording>().unwrap_err(),
+            "line 1: position (-5, 0) is off screen"
+        );
+        assert_eq!(
+            "-1 0 score 3".parse::<Recording>().unwrap_err(),
+            "line 1: invalid number \"-1\""
+        );
+
+        let mut recording = Recording::default();
+        recording.record(
+            0,
+            0,
+            Event::Tile {
+                x: -5,
+                y: 0,
+                tile: Tile::Wall,
+            },
+        );
+        assert_eq!(
+            Replay::new(recording).err(),
+            Some(String::from("Position (-5, 0) is off screen"))
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use crate::Computer;
+use crate::image::{Image, Palette};
+use crate::ocr;
+use crate::protocol::{Color, Movement, Protocol, Relative};
 
-#[derive(Default, Clone, Copy, PartialEq)]
-enum Direction {
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Direction {
     #[default]
     North,
     West,
@@ -54,61 +58,206 @@ impl Panel {
     }
 }
 
-type Grid = Vec<Vec<RefCell<Panel>>>;
+pub type Position = (i64, i64);
+
+// One paint-and-move instruction: the panel the robot stood on and where it
+// faced, the colour it read there, the colour it painted and how it moved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Move {
+    pub position: Position,
+    pub facing: Direction,
+    pub color_read: Color,
+    pub color_painted: Color,
+    pub movement: Movement,
+}
+
+// Every move of a paint run, in order. Moves are numbered from 0.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct History {
+    moves: Vec<Move>,
+}
+
+impl History {
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    fn at(&self, position: Position) -> impl Iterator<Item = (usize, &Move)> {
+        self.moves
+            .iter()
+            .enumerate()
+            .filter(move |(_, step)| step.position == position)
+    }
+
+    pub fn times_painted(&self, position: Position) -> usize {
+        self.at(position).count()
+    }
+
+    // The first and last move made on the panel.
+    pub fn visits(&self, position: Position) -> Option<(usize, usize)> {
+        let mut visits = self.at(position).map(|(number, _)| number);
+        let first = visits.next()?;
+        Some((first, visits.last().unwrap_or(first)))
+    }
+
+    pub fn visit_counts(&self) -> HashMap<Position, usize> {
+        let mut counts = HashMap::new();
+        for step in &self.moves {
+            *counts.entry(step.position).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // Visit counts over the visited area: blank for none, 1-9, then `*`.
+    pub fn heatmap(&self) -> String {
+        let counts = self.visit_counts();
+        let (Some(x0), Some(x1), Some(y0), Some(y1)) = (
+            counts.keys().map(|(x, _)| *x).min(),
+            counts.keys().map(|(x, _)| *x).max(),
+            counts.keys().map(|(_, y)| *y).min(),
+            counts.keys().map(|(_, y)| *y).max(),
+        ) else {
+            return String::new();
+        };
+        let mut heatmap = String::new();
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                heatmap.push(match counts.get(&(x, y)) {
+                    None => ' ',
+                    Some(count @ 1..=9) => char::from_digit(*count as u32, 10).unwrap(),
+                    Some(_) => '*',
+                });
+            }
+            heatmap.push('\n');
+        }
+        heatmap
+    }
+}
+
+// Only panels the robot has been on or next to are stored; every other
+// panel of the hull is black and unpainted.
+type Grid = HashMap<Position, RefCell<Panel>>;
+
+const START: Position = (0, 0);
 
 #[derive(Default)]
 struct Robot {
-    position_x: usize,
-    position_y: usize,
+    position_x: i64,
+    position_y: i64,
     facing: Direction,
     grid: Grid,
+    history: History,
 }
 
 impl Robot {
-    fn default() -> Self {
-        let grid = vec![vec![RefCell::new(Panel::default()); 45]; 6];
-        grid[0][0].borrow_mut().paint_color(1);
-        Robot {
-            position_x: 0,
-            position_y: 0,
-            facing: Direction::North,
-            grid,
+    // Stores the panel under the robot and its neighbours, so the day 15
+    // search can walk every panel the robot has been next to.
+    fn reveal_around(&mut self) {
+        let (x, y) = (self.position_x, self.position_y);
+        for position in [(x, y), (x, y - 1), (x - 1, y), (x, y + 1), (x + 1, y)] {
+            self.grid.entry(position).or_default();
         }
     }
 
-    fn set_position(&mut self, x: usize, y: usize) {
+    fn set_position(&mut self, x: i64, y: i64) {
         self.position_x = x;
         self.position_y = y;
+        self.reveal_around();
     }
 
-    fn new(width: usize, height: usize) -> Self {
-        let grid = vec![vec![RefCell::new(Panel::default()); width]; height];
-        Robot {
-            position_x: width/2,
-            position_y: height/2,
-            facing: Direction::North,
-            grid,
-        }
+    fn new() -> Self {
+        let mut robot = Robot::default();
+        robot.set_position(START.0, START.1);
+        robot
     }
     pub fn get_facing(&self) -> i64 {
       self.get_direction(self.facing)
         }
 
+    // The smallest rectangle holding every painted panel.
+    fn painted_bounds(&self) -> Option<(Position, Position)> {
+        let mut painted = self
+            .grid
+            .iter()
+            .filter(|(_, panel)| panel.borrow().painted)
+            .map(|(position, _)| *position);
+        let first = painted.next()?;
+        Some(
+            painted.fold((first, first), |((x0, y0), (x1, y1)), (x, y)| {
+                ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y)))
+            }),
+        )
+    }
+
     pub fn print_grid(&self) {
-        for row in &self.grid {
-            for cell in row {
-                cell.borrow().print();
+        let Some(((x0, y0), (x1, y1))) = self.painted_bounds() else {
+            return;
+        };
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                match self.grid.get(&(x, y)) {
+                    Some(cell) => cell.borrow().print(),
+                    None => print!(" "),
+                }
             }
             println!();
         }
     }
-    fn turn(&mut self, direction: i64) {
-        match direction {
-            0 => self.facing.turn_left(),
-            1 => self.facing.turn_right(),
-            _ => {
-                println!("cheeeee")
-            }
+    pub fn to_image(&self, palette: &Palette, scale: usize) -> Image {
+        let Some(((x0, y0), (x1, y1))) = self.painted_bounds() else {
+            return Image::from_grid(0, 0, |_, _| 0, palette, scale);
+        };
+        Image::from_grid(
+            (x1 - x0 + 1) as usize,
+            (y1 - y0 + 1) as usize,
+            |x, y| {
+                self.grid
+                    .get(&(x0 + x as i64, y0 + y as i64))
+                    .map_or(0, |panel| panel.borrow().color)
+            },
+            palette,
+            scale,
+        )
+    }
+
+    // White panels as lit cells, cropped to the white area.
+    fn white_mask(&self) -> Vec<Vec<bool>> {
+        let white: Vec<Position> = self
+            .grid
+            .iter()
+            .filter(|(_, panel)| panel.borrow().color == 1)
+            .map(|(position, _)| *position)
+            .collect();
+        let (Some(x0), Some(x1), Some(y0), Some(y1)) = (
+            white.iter().map(|(x, _)| *x).min(),
+            white.iter().map(|(x, _)| *x).max(),
+            white.iter().map(|(_, y)| *y).min(),
+            white.iter().map(|(_, y)| *y).max(),
+        ) else {
+            return Vec::new();
+        };
+        (y0..=y1)
+            .map(|y| (x0..=x1).map(|x| white.contains(&(x, y))).collect())
+            .collect()
+    }
+
+    fn read_letters(&self) -> Result<String, String> {
+        ocr::read(&self.white_mask())
+    }
+
+    fn turn(&mut self, movement: Movement) {
+        match movement {
+            Movement::TurnLeft => self.facing.turn_left(),
+            Movement::TurnRight => self.facing.turn_right(),
+            Movement::Face(direction) => self.facing = direction,
         }
     }
 
@@ -125,36 +274,21 @@ impl Robot {
         }
     }
 
-    fn calculate_path_length(&self, panel: &RefCell<Panel>) -> i32 {
-        let mut length = 0;
-        let mut current_panel = panel;
-        let mut reference_to_parent_panel = current_panel.borrow().reference_to_parent_panel.clone();
-        while let Some(parent_panel) = reference_to_parent_panel {
-            current_panel = &parent_panel;
-            length += 1;
-            current_panel.borrow_mut().paint_color(0);
-            self.print_grid();
-            reference_to_parent_panel = current_panel.borrow().reference_to_parent_panel.clone();
-        }
-        length
-    }
-
     pub fn get_shortest_path_to_finish_from_start(&mut self) -> i32 {
-        for panel in self.grid.iter().flat_map(|row| row.iter()) {
+        for panel in self.grid.values() {
                                 let mut panel_mut = panel.borrow_mut();
                                 panel_mut.painted = false;
 
                             }
-        let mut path: Vec<(RefCell<Panel>,usize, usize,usize)> = Vec::new();
+        let mut path: Vec<(RefCell<Panel>,i64, i64,usize)> = Vec::new();
+        let (start_x, start_y) = (self.position_x, self.position_y);
         let  current = self.get_current_panel();
-        let mut tiefe = 0;
+        let tiefe = 0;
         let mut max_tiefe = 0;
-        let mut minutes = 0;
         current.borrow_mut().paint_color(0);
         current.borrow_mut().is_finish = false;
-        path.push((RefCell::new((*current.borrow()).clone()),self.position_x, self.position_y, tiefe));
-        while path.len() > 0 {
-            let  (current_panel,x,y,tiefe) = path.pop().unwrap();
+        path.push((RefCell::new((*current.borrow()).clone()),start_x, start_y, tiefe));
+        while let Some((current_panel,x,y,tiefe)) = path.pop() {
             if current_panel.borrow().is_finish {
                 current_panel.borrow_mut().is_finish = false;
             }
@@ -162,8 +296,6 @@ impl Robot {
                 max_tiefe = tiefe;
                 println!("Max Tiefe: {}", max_tiefe);
             }
-            if path.len() == 0{
-            }
             for (child_panel,x,y) in self.get_adjacent_panels(&current_panel, x,y) {
                 if !child_panel.borrow().painted {
                     child_panel.borrow_mut().paint_color(-1);
@@ -175,7 +307,7 @@ impl Robot {
         }
                                 println!("Max Tiefe: {}", max_tiefe);
 
-        return -1; // No path found
+        -1 // No path found
 
     }
 
@@ -198,7 +330,7 @@ impl Robot {
         }
     }
 
-    fn get_next_position(&self) -> (usize, usize) {
+    fn get_next_position(&self) -> (i64, i64) {
         match self.facing {
             Direction::North => (self.position_x, self.position_y - 1),
             Direction::West => (self.position_x - 1, self.position_y),
@@ -207,52 +339,36 @@ impl Robot {
         }
     }
 
-    fn get_panel(&self,xy : (usize,  usize)) -> & RefCell<Panel> {
-        &self.grid[xy.1][xy.0]
-    }
-
-    fn get_panel_in_direction(&self, direction: Direction) -> &RefCell<Panel> {
+    fn get_panel_in_direction(&mut self, direction: Direction) -> &RefCell<Panel> {
         let (x, y) = match direction {
             Direction::North => (self.position_x, self.position_y - 1),
             Direction::West => (self.position_x - 1, self.position_y),
             Direction::South => (self.position_x, self.position_y + 1),
             Direction::East => (self.position_x + 1, self.position_y),
         };
-        &self.grid[y][x]
+        self.grid.entry((x, y)).or_default()
     }
 
-    fn get_panel_in_direction_xy(&self, direction: Direction,x0:usize,y0:usize) -> (&RefCell<Panel>, usize, usize) {
+    // Panels the robot has not been next to are not stored.
+    fn get_panel_in_direction_xy(&self, direction: Direction,x0:i64,y0:i64) -> (Option<&RefCell<Panel>>, i64, i64) {
         let (x, y) = match direction {
             Direction::North => (x0, y0 - 1),
             Direction::West => (x0 - 1, y0),
             Direction::South => (x0, y0 + 1),
             Direction::East => (x0 + 1, y0),
         };
-        (&self.grid[y][x], x, y)
+        (self.grid.get(&(x, y)), x, y)
     }
 
     fn advance(&mut self) {
         //println!("{}-{}", self.position_x, self.position_y);
         (self.position_x, self.position_y) = self.get_next_position();
+        self.reveal_around();
     }
     pub fn paint_next_position_color(&mut self, color: i64) {
         let (x,y) = self.get_next_position();
-        (*self.grid[y][x].borrow_mut()).paint_color(color);
-    }
-    pub fn is_next_position_painted(&self) -> bool {
-        let (x, y) = self.get_next_position();
-        self.grid[y][x].borrow().painted
+        (*self.grid.entry((x, y)).or_default().borrow_mut()).paint_color(color);
     }
-
-    fn get_panel_at(&self, direction:Direction) -> &RefCell<Panel> {
-        match direction {
-            Direction::North => &self.grid[self.position_y - 1][self.position_x],
-            Direction::West => &self.grid[self.position_y][self.position_x - 1],
-            Direction::South => &self.grid[self.position_y + 1][self.position_x],
-            Direction::East => &self.grid[self.position_y][self.position_x + 1],
-        }
-    }
-
     fn are_there_unpainted_neighbors(&self) -> bool {
         let neighbors = [
             (self.position_x, self.position_y - 1), // North
@@ -261,7 +377,7 @@ impl Robot {
             (self.position_x + 1, self.position_y), // East
         ];
         for &(nx, ny) in &neighbors {
-            if !self.grid[ny][nx].borrow().painted {
+            if !self.grid.get(&(nx, ny)).is_some_and(|panel| panel.borrow().painted) {
                 return true;
             }
         }
@@ -272,16 +388,22 @@ impl Robot {
     }
 
      fn is_current_position_painted(&self) -> bool {
-        self.get_current_panel().borrow().painted
-    }
-    pub fn paint_turn_and_advance(&mut self, color: i64, direction: i64) -> Result<(), String> {
-        self.paint_color(color);
-        self.turn(direction);
+        self.grid.get(&(self.position_x, self.position_y)).is_some_and(|panel| panel.borrow().painted)
+    }
+    pub fn paint_turn_and_advance(&mut self, color: Color, movement: Movement) {
+        self.history.moves.push(Move {
+            position: (self.position_x, self.position_y),
+            facing: self.facing,
+            color_read: Color::try_from(self.get_current_color()).unwrap_or_default(),
+            color_painted: color,
+            movement,
+        });
+        self.paint_color(color as i64);
+        self.turn(movement);
         self.advance();
-        Ok(())
     }
 
-    fn get_adjacent_panels(&self, panel_ref: &RefCell<Panel>,x: usize,y: usize) -> Vec<(&RefCell<Panel>,usize,usize)> {
+    fn get_adjacent_panels(&self, panel_ref: &RefCell<Panel>,x: i64,y: i64) -> Vec<(&RefCell<Panel>,i64,i64)> {
         let mut adjacent_panels = Vec::new();
         let directions = [
             Direction::North,
@@ -290,8 +412,10 @@ impl Robot {
             Direction::East,
         ];
         for direction in directions.iter() {
-            let (next_panel,x,y) = self.get_panel_in_direction_xy(*direction,x,y);
-            if (next_panel.borrow().color == 0) && next_panel.borrow().painted == false {
+            let (Some(next_panel),x,y) = self.get_panel_in_direction_xy(*direction,x,y) else {
+                continue;
+            };
+            if (next_panel.borrow().color == 0) && !next_panel.borrow().painted {
                 next_panel.borrow_mut().reference_to_parent_panel = Some(Rc::new(panel_ref.clone()));
                 adjacent_panels.push((next_panel,x,y));
             }
@@ -304,80 +428,91 @@ impl Robot {
         //     .map(|row| row.iter().map(|cell| (*cell.borrow()).painted).map(|value| 1 if value))
         //     .sum()
         let mut total = 0;
-        for row in &self.grid {
-            for column in row {
-                if column.borrow().painted {
-                    total += 1;
-                }
+        for panel in self.grid.values() {
+            if panel.borrow().painted {
+                total += 1;
             }
         }
         total
     }
     pub fn get_current_color(&self) -> i64 {
-        self.get_current_panel().borrow().color
+        self.grid.get(&(self.position_x, self.position_y)).map_or(0, |panel| panel.borrow().color)
     }
-    pub fn get_current_panel(&self) -> &RefCell<Panel> {
-        &self.grid[self.position_y][self.position_x]
+    pub fn get_current_panel(&mut self) -> &RefCell<Panel> {
+        self.grid.entry((self.position_x, self.position_y)).or_default()
     }
 }
 
-#[derive(Default)]
 pub struct Scenario {
     computer: Computer,
     robot: Robot,
+    protocol: Box<dyn Protocol>,
 }
 
 impl Scenario {
-    pub fn new(memory: Vec<i64>, width: usize, height: usize) -> Self {
+    pub fn new(memory: Vec<i64>) -> Self {
+        Scenario::with_protocol(memory, Box::new(Relative))
+    }
+
+    pub fn with_protocol(memory: Vec<i64>, protocol: Box<dyn Protocol>) -> Self {
         Scenario {
             computer: Computer::new(memory),
-            robot: Robot::new(width, height),
+            robot: Robot::new(),
+            protocol,
         }
     }
-    pub fn execute_paint_scenario(&mut self) -> usize {
+
+    // The starting panel gets `starting_color` without counting as painted.
+    // Stops at the first command the protocol rejects, reporting the number
+    // of the move it would have been in the history.
+    pub fn execute_paint_scenario(&mut self, starting_color: Color) -> Result<usize, String> {
+        self.robot.get_current_panel().borrow_mut().color = starting_color as i64;
         let mut run_scenario = true;
         while run_scenario {
-            let terminate = self.computer.process(self.robot.get_current_color(), true);
+            let terminate = self.computer.process(self.robot.get_current_color(), true)?;
             if terminate {
                 break;
             }
             let color = self.computer.get_output();
-            self.computer.process(0, true);
-            let direction = self.computer.get_output();
-            let _ = self.robot.paint_turn_and_advance(color, direction);
+            self.computer.process(0, true)?;
+            let movement = self.computer.get_output();
+            let (color, movement) = self
+                .protocol
+                .decode(color, movement)
+                .map_err(|error| format!("Move {}: {}", self.robot.history.len(), error))?;
+            self.robot.paint_turn_and_advance(color, movement);
             run_scenario = !self.computer.is_finished();
         }
-        self.robot.how_many_painted()
-    } 
+        Ok(self.robot.how_many_painted())
+    }
     
     pub fn push_adjacent_directions(
-        &self,
+        &mut self,
         directions_stack: &mut Vec<Direction>) {
         directions_stack.push(self.robot.get_oposite_direction());
         for i in 1..=4 {
             let direction = self.robot.get_direction_from_int(i as i64);
             if direction != self.robot.get_oposite_direction()
              && !self.robot.get_panel_in_direction(direction).borrow().painted {
+                let current_panel = self.robot.get_current_panel().clone();
                 let  mut panel_in_direction = self.robot.get_panel_in_direction(direction).borrow_mut();
-               panel_in_direction.reference_to_parent_panel = Some(Rc::new(self.robot.get_current_panel().clone()));
+               panel_in_direction.reference_to_parent_panel = Some(Rc::new(current_panel));
                 directions_stack.push(direction);
              }
         }      
     }
 
-    pub fn execute_search_oxigen(&mut self)  {
-        let mut color = 3;
-        self.robot.set_position(self.robot.grid[0].len()/2, self.robot.grid.len()/2);
+    pub fn execute_search_oxigen(&mut self) -> Result<(), String> {
+        self.robot.set_position(START.0, START.1);
         self.robot.paint_color(-1); // Start with the initial color painted
         let mut directions_stack = Vec::<Direction>::new();
         let mut rounds = 1;
         self.push_adjacent_directions(&mut directions_stack );
         while rounds >= 0
         {
-            while directions_stack.len() > 0 {
-                let try_this_direction =directions_stack.pop().unwrap();
+            while let Some(try_this_direction) = directions_stack.pop() {
                 self.robot.set_direction(try_this_direction);
-                let terminate = self.computer.process(self.robot.get_facing(), true);
+                let terminate = self.computer.process(self.robot.get_facing(), true)?;
                 if terminate {
                     break;
                 }
@@ -387,27 +522,19 @@ impl Scenario {
                         self.robot.paint_next_position_color(1);
                     },                   
                     1 => {
-                        if !self.robot.are_there_unpainted_neighbors() {
-                            if directions_stack.len() != 1 {
-                                self.robot.paint_color(0);}
+                        if !self.robot.are_there_unpainted_neighbors() && directions_stack.len() != 1 {
+                            self.robot.paint_color(0);
                         }
                         self.robot.advance();
                         if !self.robot.is_current_position_painted(){
                             self.push_adjacent_directions(&mut directions_stack);
                             self.robot.paint_color(3);
                         }
-                        else{
-                            
-                            color-=1;
-                        }
-                        
-                        color+=1;
-                        
                     },
                     2 => {self.robot.paint_color(0);self.robot.advance();self.robot.paint_color(0);
                         if rounds > 0{
                             rounds -= 1;
-                            for panel in self.robot.grid.iter().flat_map(|row| row.iter()) {
+                            for panel in self.robot.grid.values() {
                                 let mut panel_mut = panel.borrow_mut();
                                 panel_mut.painted = false;
 
@@ -435,28 +562,161 @@ impl Scenario {
                }
                
         println!("Result: {}", self.robot.get_shortest_path_to_finish_from_start());
+        Ok(())
     }
 
     pub fn print_grid(&self) {
         self.robot.print_grid();
     }
+
+    pub fn read_letters(&self) -> Result<String, String> {
+        self.robot.read_letters()
+    }
+
+    pub fn history(&self) -> &History {
+        &self.robot.history
+    }
+
+    pub fn to_image(&self, palette: &Palette, scale: usize) -> Image {
+        self.robot.to_image(palette, scale)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::AbsoluteHeading;
 
     #[test]
     fn test_from_integers() {
-        let mut robot = Robot::default();
-        let _ = robot.paint_turn_and_advance(1, 0);
-        let _ = robot.paint_turn_and_advance(0, 0);
-        let _ = robot.paint_turn_and_advance(1, 0);
-        let _ = robot.paint_turn_and_advance(1, 0);
-        let _ = robot.paint_turn_and_advance(0, 1);
-        let _ = robot.paint_turn_and_advance(1, 0);
-        let _ = robot.paint_turn_and_advance(1, 0);
+        let mut robot = Robot::new();
+        let (white, black) = (Color::White, Color::Black);
+        robot.paint_turn_and_advance(white, Movement::TurnLeft);
+        robot.paint_turn_and_advance(black, Movement::TurnLeft);
+        robot.paint_turn_and_advance(white, Movement::TurnLeft);
+        robot.paint_turn_and_advance(white, Movement::TurnLeft);
+        robot.paint_turn_and_advance(black, Movement::TurnRight);
+        robot.paint_turn_and_advance(white, Movement::TurnLeft);
+        robot.paint_turn_and_advance(white, Movement::TurnLeft);
 
         assert_eq!(robot.how_many_painted(), 6);
     }
+
+    #[test]
+    fn test_unrevealed_panel() {
+        let mut robot = Robot::new();
+        robot.position_x = 10;
+        assert_eq!(robot.get_current_color(), 0);
+        assert!(!robot.is_current_position_painted());
+        assert!(robot.are_there_unpainted_neighbors());
+        robot.paint_next_position_color(1);
+        robot.paint_color(1);
+        assert_eq!(robot.get_current_color(), 1);
+        assert_eq!(robot.how_many_painted(), 2);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut robot = Robot::new();
+        for (color, turn) in [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)] {
+            let (color, movement) = Relative.decode(color, turn).unwrap();
+            robot.paint_turn_and_advance(color, movement);
+        }
+        let history = &robot.history;
+        assert_eq!(history.len(), 7);
+        assert_eq!(
+            history.moves()[4],
+            Move {
+                position: (0, 0),
+                facing: Direction::North,
+                color_read: Color::White,
+                color_painted: Color::Black,
+                movement: Movement::TurnRight,
+            }
+        );
+        assert_eq!(history.times_painted((0, 0)), 2);
+        assert_eq!(history.visits((0, 0)), Some((0, 4)));
+        assert_eq!(history.visits((-1, 0)), Some((1, 1)));
+        assert_eq!(history.visits((5, 5)), None);
+        assert_eq!(history.heatmap(), "  1\n121\n11 \n");
+    }
+
+    #[test]
+    fn test_protocols() {
+        let program = vec![3, 100, 104, 1, 104, 1, 3, 100, 104, 1, 104, 2, 99];
+        let mut scenario = Scenario::with_protocol(program, Box::new(AbsoluteHeading));
+        assert_eq!(scenario.execute_paint_scenario(Color::Black), Ok(2));
+        let facings: Vec<Direction> = scenario
+            .history()
+            .moves()
+            .iter()
+            .map(|step| step.facing)
+            .collect();
+        assert_eq!(facings, vec![Direction::North, Direction::East]);
+        assert_eq!((scenario.robot.position_x, scenario.robot.position_y), (1, 1));
+
+        let mut scenario = Scenario::new(vec![3, 100, 104, 1, 104, 7, 99]);
+        assert_eq!(
+            scenario.execute_paint_scenario(Color::Black),
+            Err(String::from("Move 0: Unknown turn code 7"))
+        );
+        let program = vec![3, 100, 104, 1, 104, 0, 3, 100, 104, 5, 104, 0, 99];
+        let mut scenario = Scenario::new(program);
+        assert_eq!(
+            scenario.execute_paint_scenario(Color::White),
+            Err(String::from("Move 1: Unknown colour 5"))
+        );
+        assert_eq!(scenario.history().len(), 1);
+    }
+
+    #[test]
+    fn test_painted_bounds() {
+        let mut robot = Robot::new();
+        assert_eq!(robot.painted_bounds(), None);
+        for _ in 0..3 {
+            robot.advance();
+        }
+        robot.paint_color(1);
+        robot.set_position(-2, -1);
+        robot.paint_color(0);
+        assert_eq!(robot.painted_bounds(), Some(((-2, -3), (0, -1))));
+        assert_eq!(robot.how_many_painted(), 2);
+    }
+
+    #[test]
+    fn test_read_letters() {
+        let mut robot = Robot::new();
+        let rows = [
+            "#..#.####",
+            "#..#.#...",
+            "####.###.",
+            "#..#.#...",
+            "#..#.#...",
+            "#..#.####",
+        ];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                robot.set_position(x as i64 + 3, y as i64 - 2);
+                robot.paint_color(if cell == '#' { 1 } else { 0 });
+            }
+        }
+        assert_eq!(robot.read_letters(), Ok(String::from("HE")));
+
+        robot.set_position(4, -2);
+        robot.paint_color(1);
+        let error = robot.read_letters().unwrap_err();
+        assert!(error.starts_with("Unknown glyph at column 0:\n##.#\n"));
+    }
+
+    #[test]
+    fn test_hull_image() {
+        let mut robot = Robot::new();
+        robot.paint_color(0);
+        robot.set_position(2, -1);
+        robot.paint_color(1);
+        robot.set_position(0, -1);
+        robot.paint_color(0);
+        let image = robot.to_image(&Palette::hull(), 1);
+        assert_eq!(image.to_pbm(), "P1\n3 2\n1 1 0\n1 1 1\n");
+    }
 }
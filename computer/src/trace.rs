@@ -0,0 +1,107 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceEntry {
+    pub iteration: usize,
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+    pub value: i64,
+}
+
+// Every value passed between amplifiers, in the order it was passed.
+// `from: None` is the initial signal, `to: None` the circuit output.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SignalTrace {
+    entries: Vec<TraceEntry>,
+}
+
+pub fn amplifier_name(index: Option<usize>, end: &str) -> String {
+    match index {
+        Some(i) if i < 26 => char::from(b'A' + i as u8).to_string(),
+        Some(i) => i.to_string(),
+        None => end.to_string(),
+    }
+}
+
+impl SignalTrace {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn record(&mut self, iteration: usize, from: Option<usize>, to: Option<usize>, value: i64) {
+        self.entries.push(TraceEntry {
+            iteration,
+            from,
+            to,
+            value,
+        });
+    }
+
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.entries.last().map_or(0, |entry| entry.iteration + 1)
+    }
+
+    fn describe(entry: &TraceEntry) -> (String, String) {
+        (
+            amplifier_name(entry.from, "input"),
+            amplifier_name(entry.to, "output"),
+        )
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("iteration,from,to,value\n");
+        for entry in &self.entries {
+            let (from, to) = Self::describe(entry);
+            csv += &format!("{},{},{},{}\n", entry.iteration, from, to, entry.value);
+        }
+        csv
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "{:>9}  {:>6}  {:>6}  {:>12}\n",
+            "iteration", "from", "to", "value"
+        );
+        for entry in &self.entries {
+            let (from, to) = Self::describe(entry);
+            table += &format!(
+                "{:>9}  {:>6}  {:>6}  {:>12}\n",
+                entry.iteration, from, to, entry.value
+            );
+        }
+        table
+    }
+
+    // One row per transfer, one value column per trace, so runs with
+    // different phase settings can be read side by side.
+    pub fn compare(traces: &[(String, &SignalTrace)]) -> String {
+        let mut table = format!("{:>9}  {:>6}  {:>6}", "iteration", "from", "to");
+        for (label, _) in traces {
+            table += &format!("  {:>12}", label);
+        }
+        table.push('\n');
+        let rows = traces
+            .iter()
+            .map(|(_, trace)| trace.entries.len())
+            .max()
+            .unwrap_or(0);
+        for row in 0..rows {
+            let Some(entry) = traces.iter().find_map(|(_, trace)| trace.entries.get(row)) else {
+                continue;
+            };
+            let (from, to) = Self::describe(entry);
+            table += &format!("{:>9}  {:>6}  {:>6}", entry.iteration, from, to);
+            for (_, trace) in traces {
+                let value = trace
+                    .entries
+                    .get(row)
+                    .map_or(String::from("-"), |entry| entry.value.to_string());
+                table += &format!("  {:>12}", value);
+            }
+            table.push('\n');
+        }
+        table
+    }
+}
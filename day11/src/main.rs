@@ -1,13 +1,23 @@
+use computer::protocol::Color;
 use computer::robot::Scenario;
-use parser::Parser;
-use std::path::Path; // 0.8.2
+use std::process;
 
-static FILE_PATH: &str = "day11/data/input.txt";
+fn run() -> Result<(), String> {
+    let reseted_memory: Vec<i64> = parser::load_input!();
 
-fn main() {
-    let reseted_memory: Vec<i64> = Parser::from_txt_signed(Path::new(FILE_PATH));
+    let mut code = Scenario::new(reseted_memory.clone());
+    println!("Part 1: {}", code.execute_paint_scenario(Color::Black)?);
 
     let mut code = Scenario::new(reseted_memory);
-    println!("RESULT {}", code.execute_paint_scenario());
+    code.execute_paint_scenario(Color::White)?;
     code.print_grid();
+    println!("Part 2: {}", code.read_letters()?);
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
@@ -1,12 +1,47 @@
-use computer::arcade::Scenario;
+use computer::arcade::{GameResult, Scenario};
+use computer::joystick::{self, FollowBall, Hold, JoystickStrategy, PredictTrajectory};
 use parser::Parser;
-use std::path::Path; // 0.8.2
+use std::process;
 
-static FILE_PATH: &str = "day13/data/input.txt";
+fn play(memory: &[i64], patch: &str) -> Result<GameResult, String> {
+    let mut memory = memory.to_vec();
+    Parser::patch_from_txt(&parser::data_path!(patch)).apply(&mut memory);
+    Scenario::new(memory).run_headless()
+}
 
-fn main() {
-    let reseted_memory: Vec<i64> = Parser::from_txt_signed(Path::new(FILE_PATH));
+fn run() -> Result<(), String> {
+    let reseted_memory: Vec<i64> = parser::load_input!();
+
+    let part1 = play(&reseted_memory, "part1.patch")?;
+    println!("Part 1: {}", part1.blocks_remaining);
+
+    let part2 = play(&reseted_memory, "free_play.patch")?;
+    println!("Part 2: {}", part2.score);
+    println!(
+        "{:?} after {} frames, {} blocks broken",
+        part2.status, part2.frames, part2.blocks_broken
+    );
 
-    let mut code = Scenario::new(reseted_memory);
-    println!("RESULT {}", code.execute_scenario());
+    let mut free_play = reseted_memory.clone();
+    Parser::patch_from_txt(&parser::data_path!("free_play.patch")).apply(&mut free_play);
+    let strategies: Vec<(String, Box<dyn JoystickStrategy>)> = vec![
+        (String::from("hold"), Box::new(Hold)),
+        (String::from("follow"), Box::new(FollowBall)),
+        (
+            String::from("predict"),
+            Box::new(PredictTrajectory::default()),
+        ),
+    ];
+    print!(
+        "{}",
+        joystick::comparison_table(&joystick::compare(&free_play, strategies)?)
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
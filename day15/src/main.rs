@@ -1,12 +1,12 @@
 use computer::robot::Scenario;
-use parser::Parser;
-use std::path::Path; // 0.8.2
-
-static FILE_PATH: &str = "day15/data/input.txt";
+use std::process;
 
 fn main() {
-    let reseted_memory: Vec<i64> = Parser::from_txt_signed(Path::new(FILE_PATH));
+    let reseted_memory: Vec<i64> = parser::load_input!();
 
-    let mut code = Scenario::new(reseted_memory, 45, 45);
-    code.execute_search_oxigen();
+    let mut code = Scenario::new(reseted_memory);
+    if let Err(error) = code.execute_search_oxigen() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
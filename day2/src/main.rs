@@ -1,11 +1,14 @@
 use computer::Computer;
 use parser::Parser;
-use std::path::Path;
+use std::process;
 
-static FILE_PATH: &str = "day2/data/input.txt";
+fn run() -> Result<(), String> {
+    let reseted_memory: Vec<i64> = parser::load_input!();
 
-fn main() {
-    let reseted_memory: Vec<i64> = Parser::from_txt(Path::new(FILE_PATH));
+    let mut code = Computer::new(reseted_memory.clone());
+    code.apply_patch(&Parser::patch_from_txt(&parser::data_path!("part1.patch")));
+    code.process(0, false)?;
+    println!("Part 1: {}", code.get_first_position());
 
     'outer: for i in 0..=99 {
         for j in 0..=99 {
@@ -13,7 +16,7 @@ fn main() {
             memory_it[1] = i;
             memory_it[2] = j;
             let mut code = Computer::new(memory_it);
-            code.process(0, false);
+            code.process(0, false)?;
 
             if code.get_first_position() == 19690720 {
                 println!("i: {}", i);
@@ -23,4 +26,12 @@ fn main() {
             }
         }
     }
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
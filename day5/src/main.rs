@@ -1,13 +1,18 @@
 use computer::Computer;
-use parser::Parser;
-use std::path::Path;
+use std::process;
 
-static FILE_PATH: &str = "day5/data/input.txt";
-
-fn main() {
-    let reseted_memory: Vec<i64> = Parser::from_txt_signed(Path::new(FILE_PATH));
+fn run() -> Result<(), String> {
+    let reseted_memory: Vec<i64> = parser::load_input!();
 
     let mut code = Computer::new(reseted_memory);
-    code.process(5, false);
+    code.process(5, false)?;
     println!("{}", code.get_output());
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
@@ -1,23 +1,30 @@
-use computer::amplifier::AmplificationCircuit;
-use itertools::Itertools;
-use parser::Parser;
-use std::path::Path; // 0.8.2
+use computer::amplifier::{AmplificationCircuit, CircuitMode};
+use std::process;
 
-static FILE_PATH: &str = "day7/data/input.txt";
+fn run() -> Result<(), String> {
+    let amplification_program: Vec<i64> = parser::load_input!();
 
-fn main() {
-    let amplification_program: Vec<i64> = Parser::from_txt_signed(Path::new(FILE_PATH));
+    let part1 = AmplificationCircuit::optimise_phases(
+        &amplification_program,
+        &[0, 1, 2, 3, 4],
+        CircuitMode::Serial,
+        false,
+    )?;
+    println!("Part 1: {} {:?}", part1.signal, part1.phases);
 
-    let phase_settings_vector = vec![5, 6, 7, 8, 9];
-    let permutations = phase_settings_vector.into_iter().permutations(5).unique();
+    let part2 = AmplificationCircuit::optimise_phases(
+        &amplification_program,
+        &[5, 6, 7, 8, 9],
+        CircuitMode::Feedback,
+        false,
+    )?;
+    println!("Part 2: {} {:?}", part2.signal, part2.phases);
+    Ok(())
+}
 
-    let mut outputs: Vec<i64> = vec![];
-    for permutation in permutations {
-        //dbg!(&permutation);
-        let mut amplification_circuit = AmplificationCircuit::from(amplification_program.clone());
-        amplification_circuit.set_phase_setting(permutation);
-        outputs.push(amplification_circuit.process());
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
     }
-    outputs.sort();
-    println!("{}", outputs[outputs.len() - 1]);
 }
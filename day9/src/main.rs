@@ -1,13 +1,18 @@
 use computer::Computer;
-use parser::Parser;
-use std::path::Path; // 0.8.2
+use std::process;
 
-static FILE_PATH: &str = "day9/data/input.txt";
-
-fn main() {
-    let reseted_memory: Vec<i64> = Parser::from_txt_signed(Path::new(FILE_PATH));
+fn run() -> Result<(), String> {
+    let reseted_memory: Vec<i64> = parser::load_input!();
 
     let mut code = Computer::new(reseted_memory);
-    code.process(2, false);
+    code.process(2, false)?;
     println!("{}", code.get_output());
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error),
+    InvalidNumber,
+    InvalidPatch,
+    InvalidAnnotation,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, line: usize, column: usize, token: &str) -> Self {
+        ParseError {
+            file: None,
+            line,
+            column,
+            token: token.to_string(),
+            kind,
+        }
+    }
+
+    // `offset` is the byte offset of the token in the whole source line; the
+    // column is counted in characters from 1.
+    pub fn at_token(
+        kind: ParseErrorKind,
+        line_number: usize,
+        line: &str,
+        offset: usize,
+        token: &str,
+    ) -> Self {
+        let column = line
+            .get(..offset)
+            .map_or(0, |prefix| prefix.chars().count())
+            + 1;
+        Self::new(kind, line_number, column, token)
+    }
+
+    pub fn io_at_line(error: io::Error, line_number: usize) -> Self {
+        Self::new(ParseErrorKind::Io(error), line_number, 0, "")
+    }
+
+    pub fn in_file(mut self, path: &Path) -> Self {
+        self.file = Some(path.to_path_buf());
+        self
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        ParseError::new(ParseErrorKind::Io(error), 0, 0, "")
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}", file.display())?,
+            None => write!(f, "<input>")?,
+        }
+        if self.line > 0 {
+            write!(f, ":{}", self.line)?;
+        }
+        if self.column > 0 {
+            write!(f, ":{}", self.column)?;
+        }
+        match &self.kind {
+            ParseErrorKind::Io(error) => write!(f, ": {}", error),
+            ParseErrorKind::InvalidNumber => {
+                write!(f, ": cannot parse {:?} as a number", self.token)
+            }
+            ParseErrorKind::InvalidPatch => {
+                write!(f, ": expected address=value, found {:?}", self.token)
+            }
+            ParseErrorKind::InvalidAnnotation => write!(f, ": invalid annotation {:?}", self.token),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::error::ParseError;
+use crate::parser::Parser;
+use crate::program::Program;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+pub static INPUT_FILE: &str = "input.txt";
+pub static INPUT_DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+    Path(PathBuf),
+    Embedded(&'static str),
+}
+
+impl InputSource {
+    pub fn read(&self) -> Result<Vec<i64>, ParseError> {
+        match self {
+            InputSource::Path(path) => Parser::try_from_txt(path),
+            InputSource::Embedded(text) => Parser::parse_str(text),
+        }
+    }
+
+    pub fn read_program(&self) -> Result<Program, ParseError> {
+        match self {
+            InputSource::Path(path) => Parser::try_program_from_txt(path),
+            InputSource::Embedded(text) => text.parse(),
+        }
+    }
+}
+
+fn input_override(args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--input" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--input=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn from_input_dir(input_dir: Option<OsString>, package: &str, file: &str) -> Option<PathBuf> {
+    let input_dir = PathBuf::from(input_dir?);
+    let in_package = input_dir.join(package).join(file);
+    if in_package.exists() {
+        Some(in_package)
+    } else {
+        Some(input_dir.join(file))
+    }
+}
+
+// A data file shipped with the crate, such as a patch, always read from
+// `<manifest dir>/data`. Only the puzzle input can come from elsewhere.
+pub fn data_path(manifest_dir: &str, file: &str) -> PathBuf {
+    Path::new(manifest_dir).join("data").join(file)
+}
+
+// The puzzle input: an `--input <path>` argument, then `$AOC_INPUT_DIR`, then
+// the input compiled into the binary if any, then `<manifest dir>/data`.
+pub fn locate(package: &str, manifest_dir: &str, embedded: Option<&'static str>) -> InputSource {
+    locate_with(
+        env::args(),
+        env::var_os(INPUT_DIR_VARIABLE),
+        package,
+        manifest_dir,
+        embedded,
+    )
+}
+
+fn locate_with(
+    args: impl Iterator<Item = String>,
+    input_dir: Option<OsString>,
+    package: &str,
+    manifest_dir: &str,
+    embedded: Option<&'static str>,
+) -> InputSource {
+    if let Some(path) = input_override(args) {
+        return InputSource::Path(path);
+    }
+    if let Some(path) = from_input_dir(input_dir, package, INPUT_FILE) {
+        return InputSource::Path(path);
+    }
+    match embedded {
+        Some(text) => InputSource::Embedded(text),
+        None => InputSource::Path(Path::new(manifest_dir).join("data").join(INPUT_FILE)),
+    }
+}
+
+#[macro_export]
+macro_rules! data_path {
+    ($file:expr) => {
+        $crate::input::data_path(env!("CARGO_MANIFEST_DIR"), $file)
+    };
+}
+
+// Crates that declare an `embed-input` feature get `data/input.txt` compiled
+// in when it is enabled.
+#[macro_export]
+macro_rules! input_source {
+    () => {{
+        #[cfg(feature = "embed-input")]
+        let embedded = Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/data/input.txt"
+        )));
+        #[cfg(not(feature = "embed-input"))]
+        let embedded = None;
+        $crate::input::locate(env!("CARGO_PKG_NAME"), env!("CARGO_MANIFEST_DIR"), embedded)
+    }};
+}
+
+#[macro_export]
+macro_rules! load_input {
+    () => {
+        $crate::input_source!()
+            .read()
+            .unwrap_or_else(|error| panic!("{}", error))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_resolution_order() {
+        let dir = Some(OsString::from("/inputs"));
+        let locate = |argv: &[&str], dir: Option<OsString>, embedded| {
+            locate_with(args(argv), dir, "day2", "/repo/day2", embedded)
+        };
+        assert_eq!(
+            locate(&["day2", "--input", "mine.txt"], dir.clone(), None),
+            InputSource::Path(PathBuf::from("mine.txt"))
+        );
+        assert_eq!(
+            locate(&["day2", "--input=mine.txt"], dir.clone(), None),
+            InputSource::Path(PathBuf::from("mine.txt"))
+        );
+        assert_eq!(
+            locate(&["day2"], dir, Some("1,2")),
+            InputSource::Path(PathBuf::from("/inputs/input.txt"))
+        );
+        assert_eq!(
+            locate(&["day2"], None, Some("1,2")),
+            InputSource::Embedded("1,2")
+        );
+        assert_eq!(
+            locate(&["day2"], None, None),
+            InputSource::Path(PathBuf::from("/repo/day2/data/input.txt"))
+        );
+    }
+
+    #[test]
+    fn test_read_embedded() {
+        let source = InputSource::Embedded("1,2\n3\n");
+        assert_eq!(source.read().unwrap(), vec![1, 2, 3]);
+    }
+}
@@ -1,3 +1,10 @@
+pub mod error;
+pub mod input;
 pub mod parser;
+pub mod patch;
+pub mod program;
 
-pub use parser::Parser;
+pub use error::{ParseError, ParseErrorKind};
+pub use parser::{Format, Parser};
+pub use patch::Patch;
+pub use program::{Program, SymbolTable};
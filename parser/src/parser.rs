@@ -1,36 +1,197 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::error::{ParseError, ParseErrorKind};
+use crate::patch::Patch;
+use crate::program::Program;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 
+fn is_separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+// The non-empty tokens of a line, split on commas and whitespace, with their
+// byte offsets in the line.
+pub(crate) fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_inclusive(is_separator)
+        .scan(0, |offset, piece| {
+            let start = *offset;
+            *offset += piece.len();
+            Some((start, piece.strip_suffix(is_separator).unwrap_or(piece)))
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+// `text` without surrounding whitespace, with its offset when `text` starts
+// at `offset`.
+pub(crate) fn trim_at(offset: usize, text: &str) -> (usize, &str) {
+    let start = text.len() - text.trim_start().len();
+    (offset + start, text.trim())
+}
+
 pub struct Parser {}
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Format {
+    #[default]
+    CommaSeparated,
+    OnePerLine,
+}
+
 impl Parser {
     pub fn from_txt(path: &Path) -> Vec<i64> {
-        let file = File::open(path).expect("File coudlnt be opened");
-        let reader = BufReader::new(file);
+        Self::try_from_txt(path).unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        let mut data = Vec::new();
+    pub fn from_txt_signed(path: &Path) -> Vec<i64> {
+        Self::from_txt(path)
+    }
 
-        for line in reader.lines() {
-            let line = line.expect("Cannot read line");
-            data.push(line.parse().expect("Cannot parse number"));
-        }
+    pub fn try_from_txt(path: &Path) -> Result<Vec<i64>, ParseError> {
+        let file = File::open(path).map_err(|error| ParseError::from(error).in_file(path))?;
+        Self::parse_reader(BufReader::new(file)).map_err(|error| error.in_file(path))
+    }
 
-        data
+    pub fn parse_str(text: &str) -> Result<Vec<i64>, ParseError> {
+        Self::parse_reader(text.as_bytes())
     }
 
-    pub fn from_txt_signed(path: &Path) -> Vec<i64> {
-        println!("{}", path.display());
-        let file = File::open(path).expect("File coudlnt be opened");
-        let reader = BufReader::new(file);
+    pub fn parse_stdin() -> Result<Vec<i64>, ParseError> {
+        Self::parse_reader(io::stdin().lock())
+    }
 
+    pub fn parse_reader(reader: impl BufRead) -> Result<Vec<i64>, ParseError> {
         let mut data = Vec::new();
 
-        for line in reader.lines() {
-            let line = line.expect("Cannot read line");
-            data.push(line.parse().expect("Cannot parse number"));
+        for (number, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| ParseError::io_at_line(error, number + 1))?;
+            Self::parse_line(&line, number + 1, &mut data)?;
+        }
+
+        Ok(data)
+    }
+
+    fn parse_line(line: &str, line_number: usize, data: &mut Vec<i64>) -> Result<(), ParseError> {
+        for (offset, token) in tokens(line) {
+            let value = token.parse().map_err(|_| {
+                ParseError::at_token(
+                    ParseErrorKind::InvalidNumber,
+                    line_number,
+                    line,
+                    offset,
+                    token,
+                )
+            })?;
+            data.push(value);
         }
+        Ok(())
+    }
+
+    pub fn to_string(data: &[i64], format: Format) -> String {
+        let separator = match format {
+            Format::CommaSeparated => ",",
+            Format::OnePerLine => "\n",
+        };
+        let mut text = data
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(separator);
+        text.push('\n');
+        text
+    }
 
+    pub fn to_txt(path: &Path, data: &[i64], format: Format) -> io::Result<()> {
+        fs::write(path, Self::to_string(data, format))
+    }
+
+    pub fn patch_from_txt(path: &Path) -> Patch {
+        Self::try_patch_from_txt(path).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_patch_from_txt(path: &Path) -> Result<Patch, ParseError> {
+        let text =
+            fs::read_to_string(path).map_err(|error| ParseError::from(error).in_file(path))?;
+        text.parse()
+            .map_err(|error: ParseError| error.in_file(path))
+    }
+
+    pub fn program_from_txt(path: &Path) -> Program {
+        Self::try_program_from_txt(path).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_program_from_txt(path: &Path) -> Result<Program, ParseError> {
+        let text =
+            fs::read_to_string(path).map_err(|error| ParseError::from(error).in_file(path))?;
+        text.parse()
+            .map_err(|error: ParseError| error.in_file(path))
+    }
+
+    pub fn from_txt_patched(path: &Path, patch_path: &Path) -> Vec<i64> {
+        let mut data = Self::from_txt(path);
+        Self::patch_from_txt(patch_path).apply(&mut data);
         data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_round_trip_formats() {
+        let data = vec![1, 9, 10, 3, -2, 99];
+        for (name, format) in [
+            ("comma", Format::CommaSeparated),
+            ("lines", Format::OnePerLine),
+        ] {
+            let file = format!("test_round_trip_formats_{}_{}.txt", name, process::id());
+            let path = env::temp_dir().join(file);
+            Parser::to_txt(&path, &data, format).unwrap();
+            assert_eq!(Parser::from_txt(&path), data);
+            fs::remove_file(path).unwrap();
+        }
+        assert_eq!(
+            Parser::to_string(&data, Format::CommaSeparated),
+            "1,9,10,3,-2,99\n"
+        );
+    }
+
+    #[test]
+    fn test_error_position() {
+        let error = Parser::parse_str("1,2,3\n4, 5x,6\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 4));
+        assert_eq!(error.token, "5x");
+        assert!(matches!(error.kind, ParseErrorKind::InvalidNumber));
+        assert_eq!(
+            error.to_string(),
+            "<input>:2:4: cannot parse \"5x\" as a number"
+        );
+    }
+
+    #[test]
+    fn test_read_error_line() {
+        let error = Parser::parse_reader(&b"1,2\n3\n\xff\n"[..]).unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::Io(_)));
+        assert_eq!((error.line, error.column), (3, 0));
+        assert!(error.to_string().starts_with("<input>:3: "));
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let error = Parser::try_from_txt(Path::new("does/not/exist.txt")).unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::Io(_)));
+        assert_eq!(error.file.as_deref(), Some(Path::new("does/not/exist.txt")));
+    }
+
+    #[test]
+    fn test_mixed_separators() {
+        assert_eq!(Parser::parse_str("1,9,\n10").unwrap(), vec![1, 9, 10]);
+        let file = format!("test_mixed_separators_{}.txt", process::id());
+        let path = env::temp_dir().join(file);
+        fs::write(&path, "1,9, 10\n3\t2,\n\n 99,\n").unwrap();
+        assert_eq!(Parser::from_txt(&path), vec![1, 9, 10, 3, 2, 99]);
+        fs::remove_file(path).unwrap();
+    }
+}
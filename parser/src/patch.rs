@@ -0,0 +1,105 @@
+use crate::error::{ParseError, ParseErrorKind};
+use crate::parser::trim_at;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchEntry {
+    pub label: Option<String>,
+    pub address: usize,
+    pub value: i64,
+}
+
+// One `address=value` poke per line, optionally prefixed by `label:`.
+// Blank lines and `#` comments are ignored.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Patch {
+    pub entries: Vec<PatchEntry>,
+}
+
+impl FromStr for Patch {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        for (number, full_line) in text.lines().enumerate() {
+            let (start, line) = trim_at(0, full_line.split('#').next().unwrap_or(""));
+            if line.is_empty() {
+                continue;
+            }
+            let error = |kind, (offset, token)| {
+                ParseError::at_token(kind, number + 1, full_line, offset, token)
+            };
+            let (label, poke_start, poke) = match line.split_once(':') {
+                Some((label, poke)) => (
+                    Some(label.trim().to_string()),
+                    start + label.len() + 1,
+                    poke,
+                ),
+                None => (None, start, line),
+            };
+            let (address, value) = poke
+                .split_once('=')
+                .ok_or_else(|| error(ParseErrorKind::InvalidPatch, trim_at(poke_start, poke)))?;
+            let address = trim_at(poke_start, address);
+            let value = trim_at(poke_start + poke.len() - value.len(), value);
+            entries.push(PatchEntry {
+                label,
+                address: address
+                    .1
+                    .parse()
+                    .map_err(|_| error(ParseErrorKind::InvalidNumber, address))?,
+                value: value
+                    .1
+                    .parse()
+                    .map_err(|_| error(ParseErrorKind::InvalidNumber, value))?,
+            });
+        }
+        Ok(Patch { entries })
+    }
+}
+
+impl Patch {
+    pub fn apply(&self, memory: &mut Vec<i64>) {
+        for entry in &self.entries {
+            if entry.address >= memory.len() {
+                memory.resize(entry.address + 1, 0);
+            }
+            memory[entry.address] = entry.value;
+        }
+    }
+
+    pub fn get(&self, label: &str) -> Option<&PatchEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.label.as_deref() == Some(label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_apply() {
+        let patch: Patch = "# 1202 program alarm\nnoun: 1=12\n2 = 2\n\n"
+            .parse()
+            .unwrap();
+        assert_eq!(patch.entries.len(), 2);
+        assert_eq!(patch.get("noun").unwrap().value, 12);
+        let mut memory = vec![1, 0, 0, 3];
+        patch.apply(&mut memory);
+        assert_eq!(memory, vec![1, 12, 2, 3]);
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        assert!("coins 0".parse::<Patch>().is_err());
+        let error = "0=2\n0 = two".parse::<Patch>().unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.token.as_str()),
+            (2, 5, "two")
+        );
+        let error = "  coins: 0 = x".parse::<Patch>().unwrap_err();
+        assert_eq!((error.column, error.token.as_str()), (14, "x"));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::error::{ParseError, ParseErrorKind};
+use crate::parser::{tokens, trim_at};
+use crate::patch::{Patch, PatchEntry};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub day: Option<u32>,
+    pub part: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SymbolTable {
+    addresses: BTreeMap<String, usize>,
+    names: BTreeMap<usize, String>,
+}
+
+impl SymbolTable {
+    pub fn insert(&mut self, name: &str, address: usize) -> bool {
+        if self.addresses.contains_key(name) {
+            return false;
+        }
+        self.addresses.insert(name.to_string(), address);
+        self.names
+            .entry(address)
+            .or_insert_with(|| name.to_string());
+        true
+    }
+
+    pub fn address(&self, name: &str) -> Option<usize> {
+        self.addresses.get(name.trim_start_matches('@')).copied()
+    }
+
+    pub fn name_at(&self, address: usize) -> Option<&str> {
+        self.names.get(&address).map(String::as_str)
+    }
+
+    pub fn format_address(&self, address: usize) -> String {
+        match self.name_at(address) {
+            Some(name) => format!("@{}", name),
+            None => format!("[{}]", address),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.addresses
+            .iter()
+            .map(|(name, address)| (name.as_str(), *address))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+}
+
+// Annotated `.intcode` source: the program integers separated by commas or
+// whitespace, plus
+//   # comment            ignored up to the end of the line
+//   !name/!day/!part x   metadata, on a line of its own
+//   @label               names the address of the next integer
+//   @label=value         same, and records a patch point with that value
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Program {
+    pub memory: Vec<i64>,
+    pub symbols: SymbolTable,
+    pub patch_points: Patch,
+    pub metadata: Metadata,
+}
+
+impl Program {
+    pub fn patch_point(&self, name: &str) -> Option<&PatchEntry> {
+        self.patch_points.get(name)
+    }
+
+    pub fn apply_patch_point(&mut self, name: &str) -> bool {
+        let Some(entry) = self.patch_point(name).cloned() else {
+            return false;
+        };
+        match self.memory.get_mut(entry.address) {
+            Some(cell) => {
+                *cell = entry.value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // `text` is the line without its comment, `line` the whole line.
+    fn parse_metadata(&mut self, line: &str, text: &str, number: usize) -> Result<(), ParseError> {
+        let (start, text) = trim_at(0, text);
+        let (key, value) = text[1..]
+            .split_once(char::is_whitespace)
+            .unwrap_or((&text[1..], ""));
+        let (value_start, value) = trim_at(start + text.len() - value.len(), value);
+        let invalid = |offset, token| {
+            ParseError::at_token(
+                ParseErrorKind::InvalidAnnotation,
+                number,
+                line,
+                offset,
+                token,
+            )
+        };
+        match key {
+            "name" => self.metadata.name = Some(value.to_string()),
+            "day" => {
+                self.metadata.day = Some(value.parse().map_err(|_| invalid(value_start, value))?)
+            }
+            "part" => {
+                self.metadata.part = Some(value.parse().map_err(|_| invalid(value_start, value))?)
+            }
+            _ => return Err(invalid(start + 1, key)),
+        }
+        Ok(())
+    }
+
+    fn parse_label(
+        &mut self,
+        line: &str,
+        number: usize,
+        offset: usize,
+        token: &str,
+    ) -> Result<(), ParseError> {
+        let invalid = |token| {
+            ParseError::at_token(
+                ParseErrorKind::InvalidAnnotation,
+                number,
+                line,
+                offset,
+                token,
+            )
+        };
+        let (name, value) = match token[1..].split_once('=') {
+            Some((name, value)) => {
+                let value_start = offset + token.len() - value.len();
+                let value = value.parse().map_err(|_| {
+                    ParseError::at_token(
+                        ParseErrorKind::InvalidNumber,
+                        number,
+                        line,
+                        value_start,
+                        value,
+                    )
+                })?;
+                (name, Some(value))
+            }
+            None => (&token[1..], None),
+        };
+        let address = self.memory.len();
+        let valid_name = name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if name.is_empty() || !valid_name || !self.symbols.insert(name, address) {
+            return Err(invalid(token));
+        }
+        if let Some(value) = value {
+            self.patch_points.entries.push(PatchEntry {
+                label: Some(name.to_string()),
+                address,
+                value,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut program = Program::default();
+        for (number, full_line) in text.lines().enumerate() {
+            let number = number + 1;
+            let line = full_line.split('#').next().unwrap_or("");
+            if line.trim_start().starts_with('!') {
+                program.parse_metadata(full_line, line, number)?;
+                continue;
+            }
+            for (offset, token) in tokens(line) {
+                if token.starts_with('@') {
+                    program.parse_label(full_line, number, offset, token)?;
+                    continue;
+                }
+                let value = token.parse().map_err(|_| {
+                    ParseError::at_token(
+                        ParseErrorKind::InvalidNumber,
+                        number,
+                        full_line,
+                        offset,
+                        token,
+                    )
+                })?;
+                program.memory.push(value);
+            }
+        }
+        Ok(program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+    use crate::input::data_path;
+    use std::fs;
+
+    static SOURCE: &str = "# Day 2 example
+!name 1202 Program Alarm
+!day 2
+!part 1
+1, @noun=12 9, @verb=2 10, 3,  # add
+2,3,11,0,
+99,
+@data 30,40,50
+";
+
+    #[test]
+    fn test_parse_annotated_source() {
+        let mut program: Program = SOURCE.parse().unwrap();
+        assert_eq!(
+            program.memory,
+            vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+        assert_eq!(program.metadata.name.as_deref(), Some("1202 Program Alarm"));
+        assert_eq!(
+            (program.metadata.day, program.metadata.part),
+            (Some(2), Some(1))
+        );
+        assert_eq!(program.symbols.address("@verb"), Some(2));
+        assert_eq!(program.symbols.format_address(9), "@data");
+        assert_eq!(program.symbols.format_address(4), "[4]");
+        assert!(program.apply_patch_point("noun"));
+        assert_eq!(program.memory[1], 12);
+    }
+
+    #[test]
+    fn test_invalid_annotations() {
+        let error = "1,@a 2,@a 3".parse::<Program>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 8));
+        assert!("!author me".parse::<Program>().is_err());
+        let error = "1,2\n   !day  two  # indented\n"
+            .parse::<Program>()
+            .unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.token.as_str()),
+            (2, 10, "two")
+        );
+        let error = "  \t!author me".parse::<Program>().unwrap_err();
+        assert_eq!((error.column, error.token.as_str()), (5, "author"));
+        let error = "1, @x=y 1".parse::<Program>().unwrap_err();
+        assert_eq!((error.column, error.token.as_str()), (7, "y"));
+        assert!("@x=y 1".parse::<Program>().is_err());
+    }
+
+    // The annotated image of a day's input, next to the plain input.txt.
+    fn load_day(day: &str) -> (Program, Vec<i64>) {
+        let directory = format!("{}/../{}", env!("CARGO_MANIFEST_DIR"), day);
+        let source = fs::read_to_string(data_path(&directory, "input.intcode")).unwrap();
+        let plain = Parser::from_txt(&data_path(&directory, "input.txt"));
+        (source.parse().unwrap(), plain)
+    }
+
+    #[test]
+    fn test_annotated_inputs() {
+        let (day2, plain) = load_day("day2");
+        assert_eq!(day2.memory, plain);
+        assert_eq!(day2.symbols.address("noun"), Some(1));
+        assert_eq!(day2.symbols.address("verb"), Some(2));
+        let patched: Vec<_> = day2.patch_points.entries.iter().map(|e| e.value).collect();
+        assert_eq!(patched, vec![12, 2]);
+
+        let (mut day13, plain) = load_day("day13");
+        assert_eq!(day13.metadata.day, Some(13));
+        assert_ne!(day13.memory, plain);
+        assert!(day13.apply_patch_point("coins"));
+        assert_eq!(day13.memory, plain);
+        assert_eq!(day13.symbols.address("score"), Some(386));
+        assert_eq!(day13.symbols.address("ball_x"), Some(388));
+        assert_eq!(day13.symbols.address("paddle_x"), Some(392));
+    }
+}
@@ -0,0 +1,120 @@
+use computer::Computer;
+use computer::circuit::CircuitStatus;
+use computer::circuit_file::CircuitDescription;
+use parser::Parser;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+
+static USAGE: &str = "Usage: runner <program> [--patch <file>]... [--patch-point <name>]... \
+                      [--input <value,...>]... [--disassemble]\n       \
+                      runner --circuit <file> [--dot]";
+
+#[derive(Default)]
+struct Options {
+    program: Option<PathBuf>,
+    patches: Vec<PathBuf>,
+    patch_points: Vec<String>,
+    inputs: Vec<i64>,
+    disassemble: bool,
+    circuit: Option<PathBuf>,
+    dot: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--patch" => {
+                let path = args.next().ok_or("--patch needs a file")?;
+                options.patches.push(PathBuf::from(path));
+            }
+            "--patch-point" => {
+                let name = args.next().ok_or("--patch-point needs a name")?;
+                options.patch_points.push(name);
+            }
+            "--disassemble" => options.disassemble = true,
+            "--circuit" => {
+                let path = args.next().ok_or("--circuit needs a file")?;
+                options.circuit = Some(PathBuf::from(path));
+            }
+            "--dot" => options.dot = true,
+            "--input" => {
+                let values = args.next().ok_or("--input needs a value")?;
+                for value in values.split(',') {
+                    let value = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid input value {:?}", value))?;
+                    options.inputs.push(value);
+                }
+            }
+            _ if options.program.is_none() => options.program = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn run_circuit(path: &Path, dot: bool) -> Result<(), String> {
+    let description = CircuitDescription::from_file(path)?;
+    if dot {
+        print!("{}", description.to_dot());
+        return Ok(());
+    }
+    let mut circuit = description.build()?;
+    let status = circuit.run()?;
+    for (i, node) in description.nodes.iter().enumerate() {
+        let outputs: Vec<String> = circuit
+            .outputs(i)
+            .iter()
+            .map(|value| value.to_string())
+            .collect();
+        println!("{}: {}", node.name, outputs.join(","));
+    }
+    if status == CircuitStatus::Blocked {
+        return Err(String::from("Circuit is blocked waiting for input"));
+    }
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    if let Some(path) = &options.circuit {
+        return run_circuit(path, options.dot);
+    }
+    let program = options.program.ok_or("Missing program")?;
+    let mut program =
+        Parser::try_program_from_txt(Path::new(&program)).map_err(|error| error.to_string())?;
+    for name in &options.patch_points {
+        if !program.apply_patch_point(name) {
+            return Err(format!("Unknown patch point {:?}", name));
+        }
+    }
+    let mut code = Computer::from(program);
+    for patch in &options.patches {
+        code.apply_patch(&Parser::try_patch_from_txt(patch).map_err(|error| error.to_string())?);
+    }
+    if options.disassemble {
+        for line in code.disassemble() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    code.push_inputs(options.inputs);
+    while let Some(output) = code.process_queued()? {
+        println!("{}", output);
+    }
+    if code.is_waiting_input() {
+        return Err(String::from("Program is waiting for more input"));
+    }
+    println!("Memory[0]: {}", code.get_first_position());
+    Ok(())
+}
+
+fn main() {
+    let result = parse_args(env::args().skip(1)).and_then(run);
+    if let Err(error) = result {
+        eprintln!("{}\n{}", error, USAGE);
+        process::exit(1);
+    }
+}